@@ -1,15 +1,18 @@
 //! LLM-powered agent for parsing natural language queries into QueryParams.
 
-use crate::llm::{CompletionOptions, GroqClient, LlmError, Message};
+use crate::llm::{CompletionOptions, CompletionResult, LlmError, LlmProvider, Message, ToolDefinition};
 use opensky::{Bounds, QueryParams};
-use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 const PROMPT_TEMPLATE: &str = include_str!("../resources/agent.md");
 
 /// Agent for parsing natural language queries.
+///
+/// Holds any backend behind the shared `LlmProvider` trait, so the agent
+/// itself doesn't care whether it's talking to Groq, OpenAI, or Ollama.
 pub struct Agent {
-    client: GroqClient,
+    client: Box<dyn LlmProvider>,
 }
 
 /// The type of query to execute.
@@ -46,6 +49,30 @@ pub struct ParsedQuery {
     pub params: QueryParams,
 }
 
+/// Where the conversational agent is in building up the current query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentState {
+    /// Gathering parameters; no clarification is currently pending.
+    #[default]
+    Collecting,
+    /// Required fields are missing; waiting on the user to answer a question.
+    NeedsClarification,
+    /// Enough information has been gathered to preview/execute a query.
+    Ready,
+    /// The query built from the current params is currently executing.
+    Executing,
+}
+
+/// Outcome of one incremental parse step.
+#[derive(Debug, Clone)]
+pub enum AgentTurn {
+    /// The agent needs more information before it can run a query.
+    Clarification(String),
+    /// Enough information has been gathered to preview/execute a query.
+    Ready(ParsedQuery),
+}
+
 /// Parsed parameters from LLM response.
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]  // Some fields reserved for future use
@@ -77,96 +104,258 @@ struct ParsedParams {
     bounds: Option<Vec<f64>>,
     #[serde(default)]
     time_buffer: Option<i64>,
+    /// Names of previously-gathered fields to unset, e.g. `["callsign"]`.
+    /// An omitted field keeps its current value, so this is the only way
+    /// for the model to express "unset this" rather than "didn't mention it".
+    #[serde(default)]
+    clear_fields: Vec<String>,
+}
+
+/// View of the params gathered so far, injected into the prompt so the
+/// model can patch rather than re-derive them.
+#[derive(Serialize)]
+struct ParamsPatchView<'a> {
+    icao24: &'a Option<String>,
+    start: &'a Option<String>,
+    stop: &'a Option<String>,
+    callsign: &'a Option<String>,
+    departure_airport: &'a Option<String>,
+    arrival_airport: &'a Option<String>,
+    airport: &'a Option<String>,
+    limit: Option<u32>,
+}
+
+impl<'a> From<&'a QueryParams> for ParamsPatchView<'a> {
+    fn from(params: &'a QueryParams) -> Self {
+        Self {
+            icao24: &params.icao24,
+            start: &params.start,
+            stop: &params.stop,
+            callsign: &params.callsign,
+            departure_airport: &params.departure_airport,
+            arrival_airport: &params.arrival_airport,
+            airport: &params.airport,
+            limit: params.limit,
+        }
+    }
+}
+
+/// Tool definition mirroring `ParsedParams`, forced via `tool_choice` so the
+/// model returns structured arguments instead of prose we'd have to scrape.
+fn parse_flight_query_tool() -> ToolDefinition {
+    ToolDefinition {
+        name: "parse_flight_query".to_string(),
+        description:
+            "Record the parsed OpenSky query parameters, or ask the user to clarify.".to_string(),
+        parameters: json!({
+            "type": "object",
+            "properties": {
+                "status": {
+                    "type": "string",
+                    "enum": ["ok", "clarify"],
+                    "description": "\"clarify\" if the query is still missing required information",
+                },
+                "reason": {
+                    "type": "string",
+                    "description": "Clarifying question to ask the user; required when status is \"clarify\"",
+                },
+                "query_type": {
+                    "type": "string",
+                    "enum": ["trajectory", "flights", "rawdata"],
+                },
+                "hint": {
+                    "type": "string",
+                    "description": "User-friendly description of what the query will return",
+                },
+                "icao24": {"type": "string"},
+                "start": {"type": "string", "description": "ISO-ish timestamp, e.g. 2024-01-01 00:00:00"},
+                "stop": {"type": "string"},
+                "callsign": {"type": "string"},
+                "departure_airport": {"type": "string"},
+                "arrival_airport": {"type": "string"},
+                "airport": {"type": "string"},
+                "limit": {"type": "integer"},
+                "bounds": {
+                    "type": "array",
+                    "items": {"type": "number"},
+                    "minItems": 4,
+                    "maxItems": 4,
+                    "description": "[west, south, east, north]",
+                },
+                "time_buffer": {"type": "integer"},
+                "clear_fields": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Names of previously-gathered fields to unset (e.g. [\"callsign\"]) \
+                        because the user asked to drop or reset them. Do not list a field you're \
+                        simply leaving unmentioned.",
+                },
+            },
+            "required": ["status"],
+        }),
+    }
 }
 
 impl Agent {
-    /// Create a new agent with the given Groq client.
-    pub fn new(client: GroqClient) -> Self {
+    /// Create a new agent from an already-boxed provider, e.g. one built by
+    /// `llm::init` from a `ProviderConfig`.
+    pub fn from_provider(client: Box<dyn LlmProvider>) -> Self {
         Self { client }
     }
 
-    /// Parse a natural language query into a ParsedQuery.
+    /// Incrementally patch `current_params` from a follow-up message.
     ///
-    /// Returns the parsed query with type, hint, and parameters,
-    /// along with the raw LLM response for debugging.
-    pub async fn parse_query(&self, user_query: &str) -> Result<(ParsedQuery, String), LlmError> {
-        // Build prompt with current LOCAL time injected
-        // Using local time so "yesterday" matches user expectations
+    /// Unlike a one-shot parse, this sends the params built up so far plus
+    /// recent chat history so the model can apply a *patch* ("actually make
+    /// that yesterday") instead of re-deriving the whole query. Returns
+    /// either a clarifying question or a ready-to-run `ParsedQuery`, along
+    /// with the raw LLM response for debugging.
+    ///
+    /// `system_message` overrides the default system prompt when set (see
+    /// `LlmConfig::default_system_message`). `preset_prompt` is a saved
+    /// prompt fragment (see `LlmConfig::preset`) injected ahead of
+    /// `user_query` to bias extraction for a recurring workflow.
+    pub async fn parse_incremental(
+        &self,
+        user_query: &str,
+        current_params: &QueryParams,
+        current_query_type: QueryType,
+        history: &[Message],
+        system_message: Option<&str>,
+        preset_prompt: Option<&str>,
+    ) -> Result<(AgentTurn, String), LlmError> {
         let current_local = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let current_params_json = serde_json::to_string(&ParamsPatchView::from(current_params))
+            .unwrap_or_else(|_| "{}".to_string());
+
+        let user_query = match preset_prompt {
+            Some(preset) => format!("{}\n\n{}", preset, user_query),
+            None => user_query.to_string(),
+        };
+
         let prompt = PROMPT_TEMPLATE
             .replace("{current_time}", &current_local)
-            .replace("{user_query}", user_query);
+            .replace("{current_params}", &current_params_json)
+            .replace("{user_query}", &user_query);
 
-        // Call LLM
-        let messages = vec![
-            Message::system("You are a helpful assistant."),
-            Message::user(prompt),
-        ];
+        let mut messages = vec![Message::system(system_message.unwrap_or(
+            "You are a helpful assistant that incrementally refines OpenSky query parameters. \
+             Only include fields in your JSON response that the latest message actually changes; \
+             omitted fields keep their current value.",
+        ))];
+        messages.extend(history.iter().cloned());
+        messages.push(Message::user(prompt));
 
-        let response = self
+        let completion = self
             .client
-            .complete(messages, CompletionOptions::default())
+            .complete_with_tool(messages, CompletionOptions::default(), &parse_flight_query_tool())
             .await?;
 
-        // Extract JSON from response
-        let parsed_query = self.extract_params(&response)?;
+        let raw_response = match &completion {
+            CompletionResult::ToolCall(args) => args.clone(),
+            CompletionResult::Text(text) => text.clone(),
+        };
 
-        Ok((parsed_query, response))
-    }
+        let turn = self.extract_turn(&completion, current_params, current_query_type)?;
 
-    /// Extract ParsedQuery from LLM response text.
-    fn extract_params(&self, response: &str) -> Result<ParsedQuery, LlmError> {
-        // Try to find JSON object in response
-        let re = Regex::new(r"\{[^{}]*\}").unwrap();
+        Ok((turn, raw_response))
+    }
 
-        let json_str = re
-            .find(response)
-            .map(|m| m.as_str())
-            .ok_or_else(|| LlmError::Parse("No JSON object found in response".to_string()))?;
+    /// Extract an `AgentTurn` from a completion, patching `current_params`.
+    fn extract_turn(
+        &self,
+        completion: &CompletionResult,
+        current_params: &QueryParams,
+        current_query_type: QueryType,
+    ) -> Result<AgentTurn, LlmError> {
+        // Tool-call arguments (or the JSON-object-mode fallback body) are
+        // already pure JSON - no scraping needed.
+        let json_str = match completion {
+            CompletionResult::ToolCall(args) => args.as_str(),
+            CompletionResult::Text(text) => text.as_str(),
+        };
 
-        // Parse JSON
         let parsed: ParsedParams = serde_json::from_str(json_str)
             .map_err(|e| LlmError::Parse(format!("JSON parse error: {}", e)))?;
 
-        // Check if query was unclear
-        if parsed.status == "unclear" {
-            let reason = parsed.reason.unwrap_or_else(|| "Query not clear".to_string());
-            return Err(LlmError::Parse(format!("Query unclear: {}", reason)));
+        if parsed.status == "unclear" || parsed.status == "clarify" {
+            let question = parsed
+                .reason
+                .unwrap_or_else(|| "Could you clarify your query?".to_string());
+            return Ok(AgentTurn::Clarification(question));
         }
 
-        // Extract query type (default to Trajectory for backwards compatibility)
-        let query_type = parsed.query_type.unwrap_or_default();
+        let query_type = parsed.query_type.unwrap_or(current_query_type);
 
-        // Extract hint (provide default based on query type)
-        let hint = parsed.hint.unwrap_or_else(|| match query_type {
-            QueryType::Trajectory => "Download trajectory data".to_string(),
-            QueryType::Flights => "Download flight list".to_string(),
-            QueryType::Rawdata => "Download raw ADS-B messages".to_string(),
-        });
+        // Patch: only overwrite a field when the model actually returned it.
+        let mut params = current_params.clone();
+
+        // Clears apply first so an explicit new value in the same turn wins.
+        for field in &parsed.clear_fields {
+            match field.as_str() {
+                "icao24" => params.icao24 = None,
+                "start" => params.start = None,
+                "stop" => params.stop = None,
+                "callsign" => params.callsign = None,
+                "departure_airport" => params.departure_airport = None,
+                "arrival_airport" => params.arrival_airport = None,
+                "airport" => params.airport = None,
+                "limit" => params.limit = None,
+                "bounds" => params.bounds = None,
+                _ => {}
+            }
+        }
 
-        // Convert to QueryParams
-        let mut params = QueryParams::new();
-        params.start = parsed.start;
-        params.stop = parsed.stop;
-        params.icao24 = parsed.icao24;
-        params.callsign = parsed.callsign;
-        params.departure_airport = parsed.departure_airport;
-        params.arrival_airport = parsed.arrival_airport;
-        params.airport = parsed.airport;
-        params.limit = parsed.limit;
-
-        // Convert bounds array [west, south, east, north] to Bounds struct
+        if parsed.icao24.is_some() {
+            params.icao24 = parsed.icao24;
+        }
+        if parsed.start.is_some() {
+            params.start = parsed.start;
+        }
+        if parsed.stop.is_some() {
+            params.stop = parsed.stop;
+        }
+        if parsed.callsign.is_some() {
+            params.callsign = parsed.callsign;
+        }
+        if parsed.departure_airport.is_some() {
+            params.departure_airport = parsed.departure_airport;
+        }
+        if parsed.arrival_airport.is_some() {
+            params.arrival_airport = parsed.arrival_airport;
+        }
+        if parsed.airport.is_some() {
+            params.airport = parsed.airport;
+        }
+        if parsed.limit.is_some() {
+            params.limit = parsed.limit;
+        }
         if let Some(b) = parsed.bounds {
             if b.len() == 4 {
                 params.bounds = Some(Bounds::new(b[0], b[1], b[2], b[3]));
             }
         }
 
-        Ok(ParsedQuery {
+        // A start time is required to build any query; if it's still
+        // missing after the patch, keep the conversation in the loop
+        // instead of handing back a query we know will be rejected.
+        if params.start.is_none() {
+            return Ok(AgentTurn::Clarification(
+                "What time range would you like to query?".to_string(),
+            ));
+        }
+
+        let hint = parsed.hint.unwrap_or_else(|| match query_type {
+            QueryType::Trajectory => "Download trajectory data".to_string(),
+            QueryType::Flights => "Download flight list".to_string(),
+            QueryType::Rawdata => "Download raw ADS-B messages".to_string(),
+        });
+
+        Ok(AgentTurn::Ready(ParsedQuery {
             query_type,
             hint,
             params,
-        })
+        }))
     }
 
     /// Get the model name being used.
@@ -0,0 +1,301 @@
+//! Client-side filtering and aggregation over an already-fetched `FlightData`.
+
+use opensky::FlightData;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Row-wise comparison operator for `filter_result`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOp {
+    Eq,
+    Neq,
+    Gt,
+    Lt,
+    Gte,
+    Lte,
+    Contains,
+}
+
+impl FilterOp {
+    pub fn parse(op: &str) -> Option<Self> {
+        match op {
+            "eq" => Some(Self::Eq),
+            "neq" => Some(Self::Neq),
+            "gt" => Some(Self::Gt),
+            "lt" => Some(Self::Lt),
+            "gte" => Some(Self::Gte),
+            "lte" => Some(Self::Lte),
+            "contains" => Some(Self::Contains),
+            _ => None,
+        }
+    }
+}
+
+/// Aggregation function for `aggregate_result`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggFn {
+    Count,
+    Min,
+    Max,
+    Mean,
+    Sum,
+}
+
+impl AggFn {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "count" => Some(Self::Count),
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            "mean" => Some(Self::Mean),
+            "sum" => Some(Self::Sum),
+            _ => None,
+        }
+    }
+
+    fn apply(&self, values: &[f64]) -> f64 {
+        match self {
+            AggFn::Count => values.len() as f64,
+            AggFn::Min => values.iter().cloned().fold(f64::INFINITY, f64::min),
+            AggFn::Max => values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+            AggFn::Sum => values.iter().sum(),
+            AggFn::Mean => {
+                if values.is_empty() {
+                    0.0
+                } else {
+                    values.iter().sum::<f64>() / values.len() as f64
+                }
+            }
+        }
+    }
+}
+
+fn column_index(columns: &[String], column: &str) -> Result<usize, String> {
+    columns
+        .iter()
+        .position(|c| c == column)
+        .ok_or_else(|| format!("Unknown column: {}", column))
+}
+
+/// `serde_json::Value` equality is representation-sensitive (`json!(1000) != json!(1000.0)`),
+/// so numeric cells must be compared through `as_f64()` rather than raw `Value` equality.
+fn values_equal(cell: &Value, target: &Value) -> bool {
+    match (cell.as_f64(), target.as_f64()) {
+        (Some(c), Some(t)) => c == t,
+        _ => cell == target,
+    }
+}
+
+/// Group-by key for a single cell, normalized so `1000` and `1000.0` hash the same.
+fn value_key(v: &Value) -> String {
+    match v.as_f64() {
+        Some(n) => n.to_string(),
+        None => v.to_string(),
+    }
+}
+
+fn matches(op: FilterOp, cell: &Value, target: &Value) -> bool {
+    match op {
+        FilterOp::Eq => values_equal(cell, target),
+        FilterOp::Neq => !values_equal(cell, target),
+        FilterOp::Contains => match (cell.as_str(), target.as_str()) {
+            (Some(c), Some(t)) => c.contains(t),
+            _ => false,
+        },
+        FilterOp::Gt | FilterOp::Lt | FilterOp::Gte | FilterOp::Lte => {
+            match (cell.as_f64(), target.as_f64()) {
+                (Some(c), Some(t)) => match op {
+                    FilterOp::Gt => c > t,
+                    FilterOp::Lt => c < t,
+                    FilterOp::Gte => c >= t,
+                    FilterOp::Lte => c <= t,
+                    _ => unreachable!(),
+                },
+                _ => false,
+            }
+        }
+    }
+}
+
+/// Keep only rows where `column`'s value compares true against `value`.
+pub fn filter_result(
+    data: &FlightData,
+    column: &str,
+    op: FilterOp,
+    value: &Value,
+) -> Result<FlightData, String> {
+    let columns = data.columns();
+    let idx = column_index(&columns, column)?;
+
+    let rows: Vec<Vec<Value>> = data
+        .rows()
+        .iter()
+        .filter(|row| row.get(idx).is_some_and(|cell| matches(op, cell, value)))
+        .cloned()
+        .collect();
+
+    Ok(FlightData::from_rows(columns, rows))
+}
+
+/// Group rows by `group_by` columns and reduce `agg_column` with `agg_fn` per group.
+pub fn aggregate_result(
+    data: &FlightData,
+    group_by: &[String],
+    agg_column: &str,
+    agg_fn: AggFn,
+) -> Result<FlightData, String> {
+    let columns = data.columns();
+    let group_indices: Vec<usize> = group_by
+        .iter()
+        .map(|c| column_index(&columns, c))
+        .collect::<Result<_, _>>()?;
+    let agg_idx = column_index(&columns, agg_column)?;
+
+    // Preserve first-seen group order so results read naturally.
+    let mut group_order: Vec<Vec<Value>> = Vec::new();
+    let mut groups: HashMap<Vec<String>, Vec<f64>> = HashMap::new();
+
+    for row in data.rows() {
+        let key_values: Vec<Value> = group_indices.iter().map(|&i| row[i].clone()).collect();
+        let key: Vec<String> = key_values.iter().map(value_key).collect();
+
+        if !groups.contains_key(&key) {
+            group_order.push(key_values);
+        }
+
+        let numeric = row.get(agg_idx).and_then(Value::as_f64).unwrap_or(0.0);
+        groups.entry(key).or_default().push(numeric);
+    }
+
+    let result_column = format!("{}_{}", agg_fn_name(agg_fn), agg_column);
+    let mut result_columns = group_by.to_vec();
+    result_columns.push(result_column);
+
+    let rows: Vec<Vec<Value>> = group_order
+        .into_iter()
+        .map(|key_values| {
+            let key: Vec<String> = key_values.iter().map(value_key).collect();
+            let values = &groups[&key];
+            let mut row = key_values;
+            row.push(serde_json::json!(agg_fn.apply(values)));
+            row
+        })
+        .collect();
+
+    Ok(FlightData::from_rows(result_columns, rows))
+}
+
+fn agg_fn_name(agg_fn: AggFn) -> &'static str {
+    match agg_fn {
+        AggFn::Count => "count",
+        AggFn::Min => "min",
+        AggFn::Max => "max",
+        AggFn::Mean => "mean",
+        AggFn::Sum => "sum",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_data() -> FlightData {
+        FlightData::from_rows(
+            vec!["callsign".to_string(), "altitude".to_string()],
+            vec![
+                vec![json!("ABC123"), json!(1000.0)],
+                vec![json!("ABC123"), json!(2000.0)],
+                vec![json!("XYZ789"), json!(500.0)],
+            ],
+        )
+    }
+
+    #[test]
+    fn filter_op_parse_recognizes_every_operator() {
+        assert_eq!(FilterOp::parse("eq"), Some(FilterOp::Eq));
+        assert_eq!(FilterOp::parse("neq"), Some(FilterOp::Neq));
+        assert_eq!(FilterOp::parse("gt"), Some(FilterOp::Gt));
+        assert_eq!(FilterOp::parse("lt"), Some(FilterOp::Lt));
+        assert_eq!(FilterOp::parse("gte"), Some(FilterOp::Gte));
+        assert_eq!(FilterOp::parse("lte"), Some(FilterOp::Lte));
+        assert_eq!(FilterOp::parse("contains"), Some(FilterOp::Contains));
+        assert_eq!(FilterOp::parse("bogus"), None);
+    }
+
+    #[test]
+    fn agg_fn_parse_recognizes_every_function() {
+        assert_eq!(AggFn::parse("count"), Some(AggFn::Count));
+        assert_eq!(AggFn::parse("min"), Some(AggFn::Min));
+        assert_eq!(AggFn::parse("max"), Some(AggFn::Max));
+        assert_eq!(AggFn::parse("mean"), Some(AggFn::Mean));
+        assert_eq!(AggFn::parse("sum"), Some(AggFn::Sum));
+        assert_eq!(AggFn::parse("bogus"), None);
+    }
+
+    #[test]
+    fn matches_contains_is_false_on_type_mismatch() {
+        assert!(!matches(FilterOp::Contains, &json!(42), &json!("4")));
+        assert!(matches(FilterOp::Contains, &json!("hello world"), &json!("world")));
+    }
+
+    #[test]
+    fn matches_ordering_ops_are_false_on_non_numeric_cells() {
+        assert!(!matches(FilterOp::Gt, &json!("abc"), &json!(1.0)));
+        assert!(matches(FilterOp::Gte, &json!(5.0), &json!(5.0)));
+    }
+
+    #[test]
+    fn filter_result_keeps_only_matching_rows() {
+        let data = sample_data();
+        let filtered = filter_result(&data, "callsign", FilterOp::Eq, &json!("ABC123")).unwrap();
+        assert_eq!(filtered.len(), 2);
+    }
+
+    #[test]
+    fn matches_eq_and_neq_normalize_numeric_representation() {
+        assert!(matches(FilterOp::Eq, &json!(1000), &json!(1000.0)));
+        assert!(!matches(FilterOp::Neq, &json!(1000), &json!(1000.0)));
+        assert!(matches(FilterOp::Neq, &json!(1000), &json!(2000.0)));
+    }
+
+    #[test]
+    fn filter_result_rejects_unknown_column() {
+        let data = sample_data();
+        assert!(filter_result(&data, "nope", FilterOp::Eq, &json!("x")).is_err());
+    }
+
+    #[test]
+    fn aggregate_result_preserves_first_seen_group_order() {
+        let data = sample_data();
+        let aggregated =
+            aggregate_result(&data, &["callsign".to_string()], "altitude", AggFn::Sum).unwrap();
+        let rows = aggregated.rows();
+        assert_eq!(rows[0][0], json!("ABC123"));
+        assert_eq!(rows[0][1], json!(3000.0));
+        assert_eq!(rows[1][0], json!("XYZ789"));
+        assert_eq!(rows[1][1], json!(500.0));
+    }
+
+    #[test]
+    fn aggregate_result_groups_numeric_keys_regardless_of_representation() {
+        let data = FlightData::from_rows(
+            vec!["altitude".to_string(), "speed".to_string()],
+            vec![
+                vec![json!(1000), json!(100.0)],
+                vec![json!(1000.0), json!(200.0)],
+            ],
+        );
+        let aggregated =
+            aggregate_result(&data, &["altitude".to_string()], "speed", AggFn::Sum).unwrap();
+        let rows = aggregated.rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][1], json!(300.0));
+    }
+
+    #[test]
+    fn agg_fn_apply_mean_and_sum_on_empty_slice_are_zero() {
+        assert_eq!(AggFn::Mean.apply(&[]), 0.0);
+        assert_eq!(AggFn::Sum.apply(&[]), 0.0);
+    }
+}
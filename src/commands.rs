@@ -1,13 +1,16 @@
 //! Tauri commands for OSTK - exposed to JavaScript frontend.
 
-use crate::agent::{Agent, QueryType};
-use crate::config::LlmConfig;
-use crate::llm::GroqClient;
-use crate::state::{ExecutionResult, SharedState};
+use crate::agent::{Agent, AgentState, AgentTurn, QueryType};
+use crate::analytics::{self, AggFn, FilterOp};
+use crate::config::{LlmConfig, S3Config};
+use crate::llm::{GroqClient, OpenAiClient};
+use crate::state::{ExecutionResult, JobState, SharedState};
+use std::time::Duration as StdDuration;
 use chrono::{Datelike, Duration, Local, Timelike};
 use opensky::{build_query_preview_method, build_history_query, build_flightlist_query, build_rawdata_query, Bounds, QueryParams, RawTable, Trino};
+use futures_util::StreamExt;
 use serde_json::{json, Value};
-use tauri::State;
+use tauri::{Emitter, State, Window};
 
 // ========== Query Parameter Commands ==========
 
@@ -163,6 +166,7 @@ pub async fn execute_query_async(state: State<'_, SharedState>) -> Result<Value,
 
     // Reset execution state
     app_state.reset_execution();
+    app_state.agent_state = AgentState::Executing;
     app_state.add_log("Starting query execution");
 
     // Clone params and query type for async execution
@@ -220,6 +224,7 @@ async fn execute_query_background(state: SharedState, params: QueryParams, query
                 error: e.to_string(),
             });
             app_state.execution.is_executing = false;
+            app_state.agent_state = AgentState::Ready;
             return;
         }
     };
@@ -293,6 +298,15 @@ async fn execute_query_background(state: SharedState, params: QueryParams, query
                     columns,
                 });
                 app_state.last_result = Some(data);
+
+                let timestamp = chrono::Local::now().timestamp();
+                if let Err(e) =
+                    app_state
+                        .history
+                        .add_entry(query_type, &sql, &params, row_count, timestamp)
+                {
+                    app_state.add_log(&format!("Failed to record query history: {}", e));
+                }
             }
         }
         Err(e) => {
@@ -305,6 +319,7 @@ async fn execute_query_background(state: SharedState, params: QueryParams, query
     }
 
     app_state.execution.is_executing = false;
+    app_state.agent_state = AgentState::Ready;
 }
 
 #[tauri::command]
@@ -368,6 +383,7 @@ pub async fn cancel_query(state: State<'_, SharedState>) -> Result<Value, String
     app_state.execution.status = "Cancelled".to_string();
     app_state.execution.result = Some(ExecutionResult::Cancelled { cancelled: true });
     app_state.execution.is_executing = false;
+    app_state.agent_state = AgentState::Ready;
 
     Ok(json!({"success": true, "message": "Query cancelled"}))
 }
@@ -402,6 +418,347 @@ pub async fn export_parquet(state: State<'_, SharedState>, filepath: String) ->
     }
 }
 
+// ========== Analytics Commands ==========
+
+#[tauri::command]
+pub async fn filter_result(
+    state: State<'_, SharedState>,
+    column: String,
+    op: String,
+    value: Value,
+) -> Result<Value, String> {
+    let mut app_state = state.lock().await;
+
+    let data = match &app_state.last_result {
+        Some(data) => data,
+        None => return Ok(json!({"error": "No data to filter"})),
+    };
+
+    let filter_op = FilterOp::parse(&op).ok_or_else(|| format!("Unknown operator: {}", op))?;
+    let filtered = analytics::filter_result(data, &column, filter_op, &value)?;
+
+    let row_count = filtered.len();
+    let columns = filtered.columns();
+    app_state.last_result = Some(filtered);
+
+    Ok(json!({"success": true, "row_count": row_count, "columns": columns}))
+}
+
+#[tauri::command]
+pub async fn aggregate_result(
+    state: State<'_, SharedState>,
+    group_by: Vec<String>,
+    agg_column: String,
+    agg_fn: String,
+) -> Result<Value, String> {
+    let mut app_state = state.lock().await;
+
+    let data = match &app_state.last_result {
+        Some(data) => data,
+        None => return Ok(json!({"error": "No data to aggregate"})),
+    };
+
+    let agg_fn = AggFn::parse(&agg_fn).ok_or_else(|| format!("Unknown aggregation: {}", agg_fn))?;
+    let aggregated = analytics::aggregate_result(data, &group_by, &agg_column, agg_fn)?;
+
+    let row_count = aggregated.len();
+    let columns = aggregated.columns();
+    app_state.last_result = Some(aggregated);
+
+    Ok(json!({"success": true, "row_count": row_count, "columns": columns}))
+}
+
+#[tauri::command]
+pub async fn export_to_s3(
+    state: State<'_, SharedState>,
+    key: String,
+    format: String,
+) -> Result<Value, String> {
+    let app_state = state.lock().await;
+    let data = match &app_state.last_result {
+        Some(data) => data.clone(),
+        None => return Ok(json!({"error": "No data to export"})),
+    };
+    drop(app_state);
+
+    let config = S3Config::load();
+    if !config.is_configured() {
+        return Ok(json!({"error": "S3 is not configured. Add credentials in Settings."}));
+    }
+
+    // The exporters only write to a filepath, so stage to a temp file and
+    // read it back into memory before handing bytes to the S3 client.
+    let tmp_path = std::env::temp_dir().join(format!("ostk-export-{}", uuid_like_suffix()));
+    match format.as_str() {
+        "csv" => data.to_csv(&tmp_path).map_err(|e| e.to_string())?,
+        "parquet" => data.to_parquet(&tmp_path).map_err(|e| e.to_string())?,
+        _ => return Ok(json!({"error": format!("Unknown export format: {}", format)})),
+    }
+
+    let result = std::fs::read(&tmp_path);
+    std::fs::remove_file(&tmp_path).ok();
+    let bytes = result.map_err(|e| e.to_string())?;
+
+    match crate::s3::upload_object(&config, &key, bytes).await {
+        Ok(object_key) => Ok(json!({"success": true, "key": object_key})),
+        Err(e) => Ok(json!({"error": e.to_string()})),
+    }
+}
+
+#[tauri::command]
+pub async fn get_s3_presigned_url(key: String, expires_in_secs: u64) -> Result<Value, String> {
+    let config = S3Config::load();
+    if !config.is_configured() {
+        return Ok(json!({"error": "S3 is not configured. Add credentials in Settings."}));
+    }
+
+    match crate::s3::presigned_get_url(&config, &key, StdDuration::from_secs(expires_in_secs)).await {
+        Ok(url) => Ok(json!({"url": url})),
+        Err(e) => Ok(json!({"error": e.to_string()})),
+    }
+}
+
+/// Cheap unique-enough suffix for a temp filename; not a real UUID.
+fn uuid_like_suffix() -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("{:x}", nanos)
+}
+
+// ========== Config Commands (S3) ==========
+
+#[tauri::command]
+pub fn get_s3_config() -> Result<Value, String> {
+    let config = S3Config::load();
+
+    let mask_key = |key: &Option<String>| -> String {
+        match key {
+            Some(k) if k.len() > 8 => format!("{}...{}", &k[..4], &k[k.len() - 4..]),
+            Some(_) => "****".to_string(),
+            None => "".to_string(),
+        }
+    };
+
+    Ok(json!({
+        "bucket": config.bucket,
+        "region": config.region,
+        "endpoint_url": config.endpoint_url,
+        "access_key": mask_key(&config.access_key),
+        "has_access_key": config.access_key.is_some(),
+        "secret_key": mask_key(&config.secret_key),
+        "has_secret_key": config.secret_key.is_some(),
+    }))
+}
+
+#[tauri::command]
+pub fn save_s3_config(
+    bucket: String,
+    region: String,
+    endpoint_url: Option<String>,
+    access_key: String,
+    secret_key: String,
+) -> Result<Value, String> {
+    let mut config = S3Config::load();
+
+    config.bucket = bucket;
+    config.region = region;
+    config.endpoint_url = endpoint_url.filter(|s| !s.is_empty());
+
+    if !access_key.is_empty() && !access_key.contains("...") {
+        config.access_key = Some(access_key);
+    }
+    if !secret_key.is_empty() && !secret_key.contains("...") {
+        config.secret_key = Some(secret_key);
+    }
+
+    config.save()?;
+
+    Ok(json!({"success": true}))
+}
+
+// ========== Query Queue Commands ==========
+
+#[tauri::command]
+pub async fn enqueue_query(state: State<'_, SharedState>) -> Result<Value, String> {
+    let mut app_state = state.lock().await;
+
+    if app_state.query_params.start.is_none() {
+        return Ok(json!({"error": "Start time is required"}));
+    }
+
+    let id = app_state.enqueue_job();
+    let position = app_state.queue.len() - 1;
+    let should_spawn_worker = !app_state.queue_worker_running;
+    if should_spawn_worker {
+        app_state.queue_worker_running = true;
+    }
+    drop(app_state);
+
+    if should_spawn_worker {
+        let state_clone = state.inner().clone();
+        tokio::spawn(async move {
+            run_queue_worker(state_clone).await;
+        });
+    }
+
+    Ok(json!({"job_id": id, "position": position}))
+}
+
+/// Drain `AppState::queue` sequentially, one job at a time.
+async fn run_queue_worker(state: SharedState) {
+    loop {
+        let next_job = {
+            let mut app_state = state.lock().await;
+            let job = app_state
+                .queue
+                .iter_mut()
+                .find(|j| j.state == JobState::Pending);
+            match job {
+                Some(job) => {
+                    job.state = JobState::Running;
+                    Some((job.id, job.query_params.clone(), job.query_type))
+                }
+                None => {
+                    // Clear the flag in the same critical section as the
+                    // "nothing pending" check: if we dropped the lock first,
+                    // a job enqueued in between would see the flag still set
+                    // and never spawn a new worker, stranding it as Pending.
+                    app_state.queue_worker_running = false;
+                    None
+                }
+            }
+        };
+
+        let (job_id, params, query_type) = match next_job {
+            Some(j) => j,
+            None => break,
+        };
+
+        let result = run_queued_job(&state, job_id, params.clone(), query_type).await;
+
+        let mut app_state = state.lock().await;
+        match result {
+            Ok(data) => {
+                let row_count = data.len();
+                app_state.add_job_log(job_id, &format!("Retrieved {} rows", row_count));
+
+                let sql = match query_type {
+                    QueryType::Trajectory => build_history_query(&params),
+                    QueryType::Flights => build_flightlist_query(&params),
+                    QueryType::Rawdata => build_rawdata_query(&params, RawTable::default()),
+                };
+                let timestamp = chrono::Local::now().timestamp();
+                if let Err(e) =
+                    app_state
+                        .history
+                        .add_entry(query_type, &sql, &params, row_count, timestamp)
+                {
+                    app_state.add_job_log(job_id, &format!("Failed to record query history: {}", e));
+                }
+
+                app_state.queued_results.insert(job_id, data);
+                if let Some(job) = app_state.queue.iter_mut().find(|j| j.id == job_id) {
+                    job.state = JobState::Done;
+                    job.row_count = Some(row_count);
+                }
+            }
+            Err(e) => {
+                app_state.add_job_log(job_id, &format!("Error: {}", e));
+                if let Some(job) = app_state.queue.iter_mut().find(|j| j.id == job_id) {
+                    job.state = JobState::Error;
+                }
+            }
+        }
+    }
+}
+
+/// Run a single queued job against Trino, logging into that job's own log buffer.
+async fn run_queued_job(
+    state: &SharedState,
+    job_id: u64,
+    params: QueryParams,
+    query_type: QueryType,
+) -> Result<opensky::FlightData, String> {
+    state
+        .lock()
+        .await
+        .add_job_log(job_id, "Connecting to OpenSky Trino...");
+
+    let mut trino = Trino::new().await.map_err(|e| e.to_string())?;
+    trino.set_source("ostk");
+
+    let result = match query_type {
+        QueryType::Trajectory => trino.history(params).await,
+        QueryType::Flights => trino.flightlist(params).await,
+        QueryType::Rawdata => trino.rawdata(params, RawTable::default()).await,
+    };
+
+    result.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_queue_status(state: State<'_, SharedState>) -> Result<Value, String> {
+    let app_state = state.lock().await;
+
+    let jobs: Vec<Value> = app_state
+        .queue
+        .iter()
+        .enumerate()
+        .map(|(position, job)| {
+            json!({
+                "id": job.id,
+                "position": position,
+                "state": job.state,
+                "query_type": job.query_type.to_string(),
+                "row_count": job.row_count,
+                "logs": job.logs,
+            })
+        })
+        .collect();
+
+    Ok(json!({"jobs": jobs, "running": app_state.queue_worker_running}))
+}
+
+#[tauri::command]
+pub async fn cancel_queued_job(state: State<'_, SharedState>, index: usize) -> Result<Value, String> {
+    let mut app_state = state.lock().await;
+
+    match app_state.queue.get(index) {
+        Some(job) if job.state == JobState::Pending => {
+            app_state.queue.remove(index);
+            Ok(json!({"success": true}))
+        }
+        Some(_) => Ok(json!({"error": "Only pending jobs can be cancelled"})),
+        None => Ok(json!({"error": "No job at that index"})),
+    }
+}
+
+#[tauri::command]
+pub async fn clear_queue(state: State<'_, SharedState>) -> Result<Value, String> {
+    let mut app_state = state.lock().await;
+    app_state.queue.retain(|job| job.state != JobState::Pending);
+    Ok(json!({"success": true}))
+}
+
+/// Promote a finished queued job's result into `last_result`, so the
+/// existing export/analytics commands (which only look at `last_result`)
+/// can reach data that was retrieved out-of-band by the queue worker.
+#[tauri::command]
+pub async fn load_queued_result(state: State<'_, SharedState>, job_id: u64) -> Result<Value, String> {
+    let mut app_state = state.lock().await;
+
+    let Some(data) = app_state.queued_results.remove(&job_id) else {
+        return Ok(json!({"error": "No stored result for that job id"}));
+    };
+
+    let row_count = data.len();
+    app_state.last_result = Some(data);
+
+    Ok(json!({"success": true, "row_count": row_count}))
+}
+
 // ========== Chat Commands ==========
 
 #[tauri::command]
@@ -417,16 +774,32 @@ pub async fn clear_messages(state: State<'_, SharedState>) -> Result<Value, Stri
     Ok(json!([]))
 }
 
+/// How many prior chat turns to feed back to the LLM as context for a patch.
+const HISTORY_WINDOW: usize = 12;
+
 #[tauri::command]
 pub async fn send_message(
     state: State<'_, SharedState>,
     user_message: String,
+    preset: Option<String>,
 ) -> Result<Value, String> {
-    // Add user message
-    {
+    // Add user message, and snapshot what we have so far for the incremental parse.
+    let (history, current_params, current_query_type) = {
         let mut app_state = state.lock().await;
+        let history: Vec<crate::llm::Message> = app_state
+            .messages
+            .iter()
+            .rev()
+            .take(HISTORY_WINDOW)
+            .rev()
+            .map(|m| crate::llm::Message {
+                role: m.role.clone(),
+                content: m.content.clone(),
+            })
+            .collect();
         app_state.add_message("user", &user_message, "text");
-    }
+        (history, app_state.query_params.clone(), app_state.query_type)
+    };
 
     // Load LLM config
     let config = LlmConfig::load();
@@ -443,18 +816,15 @@ pub async fn send_message(
         }));
     }
 
-    // Create agent based on provider
-    let agent = match config.provider.as_str() {
-        "groq" => {
-            let api_key = config.groq_api_key.unwrap();
-            let client = GroqClient::new(api_key, &config.groq_model);
-            Agent::new(client)
-        }
-        _ => {
+    // Build the active provider's client and resolve its model name from the registry.
+    let model_name = config.active().map(|p| p.model().to_string()).unwrap_or_default();
+    let agent = match crate::llm::init(&config) {
+        Some(client) => Agent::from_provider(client),
+        None => {
             let mut app_state = state.lock().await;
             app_state.add_message(
                 "assistant",
-                &format!("Provider '{}' not yet supported. Use Groq for now.", config.provider),
+                &format!("Unknown or unconfigured LLM provider '{}'.", config.active_provider),
                 "error",
             );
             return Ok(json!({
@@ -463,13 +833,37 @@ pub async fn send_message(
         }
     };
 
-    // Parse the query
-    match agent.parse_query(&user_message).await {
-        Ok((parsed_query, _raw_response)) => {
+    let preset_prompt = preset.as_deref().and_then(|name| config.preset(name)).map(|p| p.prompt.as_str());
+
+    // Incrementally patch the params built up so far, rather than re-deriving them.
+    match agent
+        .parse_incremental(
+            &user_message,
+            &current_params,
+            current_query_type,
+            &history,
+            config.default_system_message.as_deref(),
+            preset_prompt,
+        )
+        .await
+    {
+        Ok((AgentTurn::Clarification(question), _raw_response)) => {
+            let mut app_state = state.lock().await;
+            app_state.agent_state = AgentState::NeedsClarification;
+            app_state.add_message("assistant", &question, "text");
+
+            Ok(json!({
+                "messages": app_state.messages,
+                "agent_state": app_state.agent_state,
+            }))
+        }
+        Ok((AgentTurn::Ready(parsed_query), _raw_response)) => {
             let mut app_state = state.lock().await;
 
-            // Update query params
+            // Update query params/type
             app_state.query_params = parsed_query.params.clone();
+            app_state.query_type = parsed_query.query_type;
+            app_state.agent_state = AgentState::Ready;
 
             // Build query preview with correct method name based on query type
             let method_name = match parsed_query.query_type {
@@ -484,11 +878,12 @@ pub async fn send_message(
 
             // Store agent info
             app_state.agent_configured = true;
-            app_state.provider_name = config.provider.clone();
-            app_state.model_name = config.groq_model.clone();
+            app_state.provider_name = config.active_provider.clone();
+            app_state.model_name = model_name.clone();
 
             Ok(json!({
                 "messages": app_state.messages,
+                "agent_state": app_state.agent_state,
                 "query_type": parsed_query.query_type.to_string(),
                 "hint": parsed_query.hint,
                 "params": json!({
@@ -517,6 +912,62 @@ pub async fn send_message(
     }
 }
 
+/// Stream a plain chat completion of `user_message` token-by-token over Tauri
+/// events instead of waiting for the full completion. The message is sent
+/// as-is: no agent prompt or tool is attached and the reply is not parsed.
+///
+/// Once streaming starts, emits `llm-stream-chunk` for each content delta,
+/// then exactly one of `llm-stream-done` (with the full text) or
+/// `llm-stream-error`. A missing configuration or an unsupported provider is
+/// reported in the returned value only, with no event emitted. Ollama isn't
+/// wired up here since only the OpenAI-compatible providers share the SSE
+/// framing `OpenAiCompatClient::complete_stream` understands.
+#[tauri::command]
+pub async fn stream_chat_completion(window: Window, user_message: String) -> Result<Value, String> {
+    let config = LlmConfig::load();
+
+    if !config.is_configured() {
+        return Ok(json!({"error": "LLM not configured. Please add your API key in Settings."}));
+    }
+
+    let messages = vec![crate::llm::Message::user(user_message)];
+
+    let mut stream = match config.active() {
+        Some(crate::config::ProviderConfig::Groq { api_key, model, base_url, extra_headers }) => {
+            let api_key = api_key.clone().unwrap();
+            GroqClient::new(base_url, api_key, model)
+                .with_extra_headers(extra_headers.clone())
+                .complete_stream(messages, crate::llm::CompletionOptions::default())
+        }
+        Some(crate::config::ProviderConfig::Openai { api_key, model, base_url, extra_headers }) => {
+            let api_key = api_key.clone().unwrap();
+            OpenAiClient::new(base_url, api_key, model)
+                .with_extra_headers(extra_headers.clone())
+                .complete_stream(messages, crate::llm::CompletionOptions::default())
+        }
+        _ => {
+            return Ok(json!({"error": format!("Streaming is not supported for provider '{}'", config.active_provider)}));
+        }
+    };
+
+    let mut full_text = String::new();
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(text) => {
+                full_text.push_str(&text);
+                let _ = window.emit("llm-stream-chunk", &text);
+            }
+            Err(e) => {
+                let _ = window.emit("llm-stream-error", e.to_string());
+                return Ok(json!({"error": e.to_string()}));
+            }
+        }
+    }
+
+    let _ = window.emit("llm-stream-done", &full_text);
+    Ok(json!({"success": true, "text": full_text}))
+}
+
 // ========== Agent Status ==========
 
 #[tauri::command]
@@ -526,13 +977,9 @@ pub async fn get_agent_status(state: State<'_, SharedState>) -> Result<Value, St
 
     Ok(json!({
         "configured": config.is_configured(),
-        "provider": config.provider,
-        "model": match config.provider.as_str() {
-            "groq" => config.groq_model,
-            "openai" => config.openai_model,
-            "ollama" => config.ollama_model,
-            _ => "unknown".to_string(),
-        },
+        "provider": config.active_provider,
+        "model": config.active().map(|p| p.model().to_string()).unwrap_or_else(|| "unknown".to_string()),
+        "state": app_state.agent_state,
         "error": app_state.error_message,
     }))
 }
@@ -596,47 +1043,136 @@ pub fn get_llm_config() -> Result<Value, String> {
         }
     };
 
+    let groq = config.provider("groq");
+    let openai = config.provider("openai");
+    let ollama = config.provider("ollama");
+
+    let groq_api_key = groq.and_then(|p| match p {
+        crate::config::ProviderConfig::Groq { api_key, .. } => api_key.clone(),
+        _ => None,
+    });
+    let openai_api_key = openai.and_then(|p| match p {
+        crate::config::ProviderConfig::Openai { api_key, .. } => api_key.clone(),
+        _ => None,
+    });
+
     Ok(json!({
-        "provider": config.provider,
-        "groq_api_key": mask_key(&config.groq_api_key),
-        "groq_model": config.groq_model,
-        "has_groq_key": config.groq_api_key.is_some(),
-        "openai_api_key": mask_key(&config.openai_api_key),
-        "openai_model": config.openai_model,
-        "has_openai_key": config.openai_api_key.is_some(),
-        "ollama_base_url": config.ollama_base_url,
-        "ollama_model": config.ollama_model,
+        "provider": config.active_provider,
+        "groq_api_key": mask_key(&groq_api_key),
+        "groq_model": groq.map(|p| p.model().to_string()).unwrap_or_default(),
+        "groq_base_url": groq.map(|p| match p {
+            crate::config::ProviderConfig::Groq { base_url, .. } => base_url.clone(),
+            _ => String::new(),
+        }).unwrap_or_default(),
+        "has_groq_key": groq_api_key.is_some(),
+        "openai_api_key": mask_key(&openai_api_key),
+        "openai_model": openai.map(|p| p.model().to_string()).unwrap_or_default(),
+        "openai_base_url": openai.map(|p| match p {
+            crate::config::ProviderConfig::Openai { base_url, .. } => base_url.clone(),
+            _ => String::new(),
+        }).unwrap_or_default(),
+        "has_openai_key": openai_api_key.is_some(),
+        "ollama_base_url": ollama.map(|p| match p {
+            crate::config::ProviderConfig::Ollama { base_url, .. } => base_url.clone(),
+            _ => String::new(),
+        }).unwrap_or_default(),
+        "ollama_model": ollama.map(|p| p.model().to_string()).unwrap_or_default(),
+        "groq_extra_headers": groq.map(|p| match p {
+            crate::config::ProviderConfig::Groq { extra_headers, .. } => extra_headers.clone(),
+            _ => Default::default(),
+        }).unwrap_or_default(),
+        "openai_extra_headers": openai.map(|p| match p {
+            crate::config::ProviderConfig::Openai { extra_headers, .. } => extra_headers.clone(),
+            _ => Default::default(),
+        }).unwrap_or_default(),
+        "system_message": config.default_system_message,
     }))
 }
 
+#[tauri::command]
+pub fn save_system_message(message: Option<String>) -> Result<Value, String> {
+    let mut config = LlmConfig::load();
+    config.default_system_message = message.filter(|s| !s.is_empty());
+    config.save()?;
+    Ok(json!({"success": true}))
+}
+
+#[tauri::command]
+pub fn list_agent_presets() -> Result<Value, String> {
+    let config = LlmConfig::load();
+    Ok(json!({"presets": config.presets}))
+}
+
+#[tauri::command]
+pub fn save_agent_preset(name: String, prompt: String) -> Result<Value, String> {
+    let mut config = LlmConfig::load();
+    match config.presets.iter_mut().find(|p| p.name == name) {
+        Some(existing) => existing.prompt = prompt,
+        None => config.presets.push(crate::config::AgentPreset { name, prompt }),
+    }
+    config.save()?;
+    Ok(json!({"success": true, "presets": config.presets}))
+}
+
+#[tauri::command]
+pub fn delete_agent_preset(name: String) -> Result<Value, String> {
+    let mut config = LlmConfig::load();
+    config.presets.retain(|p| p.name != name);
+    config.save()?;
+    Ok(json!({"success": true, "presets": config.presets}))
+}
+
 #[tauri::command]
 pub fn save_llm_config(
     provider: String,
     model: String,
     api_key: String,
+    base_url: Option<String>,
+    extra_headers: Option<std::collections::HashMap<String, String>>,
 ) -> Result<Value, String> {
+    if !["groq", "openai", "ollama"].contains(&provider.as_str()) {
+        return Err(format!("Unknown provider: {}", provider));
+    }
+
     let mut config = LlmConfig::load();
 
-    config.provider = provider.clone();
+    config.active_provider = provider.clone();
+
+    let entry = config
+        .provider_mut(&provider)
+        .ok_or_else(|| format!("Unknown provider: {}", provider))?;
 
-    // Update the appropriate provider settings
-    match provider.as_str() {
-        "groq" => {
+    match entry {
+        crate::config::ProviderConfig::Groq { api_key: stored_key, model: stored_model, base_url: stored_url, extra_headers: stored_headers } => {
             if !api_key.is_empty() && !api_key.contains("...") {
-                config.groq_api_key = Some(api_key);
+                *stored_key = Some(api_key);
+            }
+            *stored_model = model;
+            if let Some(url) = base_url.filter(|s| !s.is_empty()) {
+                *stored_url = url;
+            }
+            if let Some(headers) = extra_headers {
+                *stored_headers = headers;
             }
-            config.groq_model = model;
         }
-        "openai" => {
+        crate::config::ProviderConfig::Openai { api_key: stored_key, model: stored_model, base_url: stored_url, extra_headers: stored_headers } => {
             if !api_key.is_empty() && !api_key.contains("...") {
-                config.openai_api_key = Some(api_key);
+                *stored_key = Some(api_key);
+            }
+            *stored_model = model;
+            if let Some(url) = base_url.filter(|s| !s.is_empty()) {
+                *stored_url = url;
+            }
+            if let Some(headers) = extra_headers {
+                *stored_headers = headers;
             }
-            config.openai_model = model;
         }
-        "ollama" => {
-            config.ollama_model = model;
+        crate::config::ProviderConfig::Ollama { model: stored_model, base_url: stored_url } => {
+            *stored_model = model;
+            if let Some(url) = base_url.filter(|s| !s.is_empty()) {
+                *stored_url = url;
+            }
         }
-        _ => return Err(format!("Unknown provider: {}", provider)),
     }
 
     config.save()?;
@@ -644,17 +1180,43 @@ pub fn save_llm_config(
     Ok(json!({"success": true, "provider": provider}))
 }
 
+// ========== Query History Commands ==========
+
 #[tauri::command]
-pub async fn fetch_groq_models(api_key: String) -> Result<Value, String> {
-    // Use provided key or load from config
+pub async fn search_query_history(
+    state: State<'_, SharedState>,
+    query: String,
+    limit: u32,
+) -> Result<Value, String> {
+    let app_state = state.lock().await;
+    let entries = app_state
+        .history
+        .search(&query, limit)
+        .map_err(|e| format!("Search failed: {}", e))?;
+    Ok(json!({ "entries": entries }))
+}
+
+#[tauri::command]
+pub async fn fetch_models(provider: String, api_key: String) -> Result<Value, String> {
+    let config = LlmConfig::load();
+
+    // Use provided key or fall back to the one already saved for this provider.
     let key = if api_key.is_empty() || api_key.contains("...") {
-        let config = LlmConfig::load();
-        config.groq_api_key.ok_or("No Groq API key configured")?
+        config.provider(&provider).and_then(|p| match p {
+            crate::config::ProviderConfig::Groq { api_key, .. } => api_key.clone(),
+            crate::config::ProviderConfig::Openai { api_key, .. } => api_key.clone(),
+            crate::config::ProviderConfig::Ollama { .. } => None,
+        })
     } else {
-        api_key
+        Some(api_key)
     };
 
-    match crate::llm::fetch_models(&key).await {
+    let base_url = config.provider(&provider).and_then(|p| match p {
+        crate::config::ProviderConfig::Ollama { base_url, .. } => Some(base_url.clone()),
+        _ => None,
+    });
+
+    match crate::llm::fetch_models(&provider, key, base_url).await {
         Ok(models) => {
             // Sort models alphabetically
             let mut sorted = models;
@@ -1,85 +1,213 @@
 //! Configuration management for OSTK.
 
 use configparser::ini::Ini;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
-/// LLM configuration.
+const DEFAULT_GROQ_BASE_URL: &str = "https://api.groq.com/openai/v1/chat/completions";
+const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1/chat/completions";
+const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+/// One provider's settings. Tagged so the registry can grow by adding a
+/// variant instead of widening `LlmConfig` into more flat fields. Adding a
+/// provider still means a new variant here, a `register_provider!` arm in
+/// `llm::init`, and a match arm in each of this type's methods below.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ProviderConfig {
+    Groq {
+        api_key: Option<String>,
+        model: String,
+        base_url: String,
+        #[serde(default)]
+        extra_headers: HashMap<String, String>,
+    },
+    Openai {
+        api_key: Option<String>,
+        model: String,
+        base_url: String,
+        #[serde(default)]
+        extra_headers: HashMap<String, String>,
+    },
+    Ollama {
+        base_url: String,
+        model: String,
+    },
+}
+
+impl ProviderConfig {
+    fn default_for(type_name: &str) -> Self {
+        match type_name {
+            "groq" => ProviderConfig::Groq {
+                api_key: None,
+                model: String::new(),
+                base_url: DEFAULT_GROQ_BASE_URL.to_string(),
+                extra_headers: HashMap::new(),
+            },
+            "openai" => ProviderConfig::Openai {
+                api_key: None,
+                model: String::new(),
+                base_url: DEFAULT_OPENAI_BASE_URL.to_string(),
+                extra_headers: HashMap::new(),
+            },
+            _ => ProviderConfig::Ollama {
+                base_url: DEFAULT_OLLAMA_BASE_URL.to_string(),
+                model: String::new(),
+            },
+        }
+    }
+
+    /// The registry key this variant is stored and looked up under.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ProviderConfig::Groq { .. } => "groq",
+            ProviderConfig::Openai { .. } => "openai",
+            ProviderConfig::Ollama { .. } => "ollama",
+        }
+    }
+
+    pub fn model(&self) -> &str {
+        match self {
+            ProviderConfig::Groq { model, .. }
+            | ProviderConfig::Openai { model, .. }
+            | ProviderConfig::Ollama { model, .. } => model,
+        }
+    }
+
+    /// Whether this provider has enough credentials to attempt a request.
+    pub fn is_configured(&self) -> bool {
+        match self {
+            ProviderConfig::Groq { api_key, .. } => api_key.is_some(),
+            ProviderConfig::Openai { api_key, .. } => api_key.is_some(),
+            ProviderConfig::Ollama { .. } => true, // no API key needed
+        }
+    }
+}
+
+/// A named, reusable prompt fragment injected ahead of the user's message,
+/// e.g. to bias the agent toward a particular query shape for a recurring
+/// workflow (`Trajectory` queries over a specific bounding box, etc.).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentPreset {
+    pub name: String,
+    pub prompt: String,
+}
+
+/// LLM configuration: which provider is active, plus every provider's own
+/// settings so switching providers in Settings doesn't lose the others'.
 #[derive(Debug, Clone, Default)]
 pub struct LlmConfig {
-    pub provider: String,
-    pub groq_api_key: Option<String>,
-    pub groq_model: String,
-    pub openai_api_key: Option<String>,
-    pub openai_model: String,
-    pub ollama_base_url: String,
-    pub ollama_model: String,
+    pub active_provider: String,
+    pub providers: Vec<ProviderConfig>,
+    /// Overrides the agent's hard-coded system message when set.
+    pub default_system_message: Option<String>,
+    pub presets: Vec<AgentPreset>,
 }
 
 impl LlmConfig {
     /// Load LLM configuration from config file.
     pub fn load() -> Self {
         let config_path = Self::config_path();
-        let mut config = LlmConfig::default();
+        let mut config = LlmConfig {
+            active_provider: "groq".to_string(),
+            providers: Vec::new(),
+            default_system_message: None,
+            presets: Vec::new(),
+        };
 
-        // Set defaults (models are empty until configured or fetched)
-        config.provider = "groq".to_string();
-        config.ollama_base_url = "http://localhost:11434".to_string();
-
-        if let Some(path) = config_path {
+        if let Some(path) = &config_path {
             if path.exists() {
                 let mut ini = Ini::new();
-                if ini.load(&path).is_ok() {
-                    // Load [llm] section
+                if ini.load(path).is_ok() {
                     if let Some(provider) = ini.get("llm", "provider") {
-                        config.provider = provider;
+                        config.active_provider = provider;
                     }
 
-                    // Groq settings
-                    if let Some(key) = ini.get("llm", "groq_api_key") {
-                        if !key.is_empty() {
-                            config.groq_api_key = Some(key);
+                    if let Some(raw) = ini.get("llm", "providers") {
+                        if let Ok(providers) = serde_json::from_str::<Vec<ProviderConfig>>(&raw) {
+                            config.providers = providers;
                         }
                     }
-                    if let Some(model) = ini.get("llm", "groq_model") {
-                        config.groq_model = model;
-                    }
 
-                    // OpenAI settings
-                    if let Some(key) = ini.get("llm", "openai_api_key") {
-                        if !key.is_empty() {
-                            config.openai_api_key = Some(key);
-                        }
-                    }
-                    if let Some(model) = ini.get("llm", "openai_model") {
-                        config.openai_model = model;
+                    if config.providers.is_empty() {
+                        // Pre-registry settings.conf had flat `groq_*`/`openai_*`/`ollama_*`
+                        // keys directly under [llm]; fold them into the registry once.
+                        config.providers = Self::migrate_legacy_keys(&ini);
                     }
 
-                    // Ollama settings
-                    if let Some(url) = ini.get("llm", "ollama_base_url") {
-                        config.ollama_base_url = url;
-                    }
-                    if let Some(model) = ini.get("llm", "ollama_model") {
-                        config.ollama_model = model;
+                    config.default_system_message =
+                        ini.get("llm", "system_message").filter(|s| !s.is_empty());
+
+                    if let Some(raw) = ini.get("llm", "presets") {
+                        if let Ok(presets) = serde_json::from_str::<Vec<AgentPreset>>(&raw) {
+                            config.presets = presets;
+                        }
                     }
                 }
             }
         }
 
+        for type_name in ["groq", "openai", "ollama"] {
+            if config.provider(type_name).is_none() {
+                config.providers.push(ProviderConfig::default_for(type_name));
+            }
+        }
+
         // Also check environment variables
-        if config.groq_api_key.is_none() {
-            if let Ok(key) = std::env::var("GROQ_API_KEY") {
-                config.groq_api_key = Some(key);
+        if let Some(ProviderConfig::Groq { api_key, .. }) = config.provider_mut("groq") {
+            if api_key.is_none() {
+                if let Ok(key) = std::env::var("GROQ_API_KEY") {
+                    *api_key = Some(key);
+                }
             }
         }
-        if config.openai_api_key.is_none() {
-            if let Ok(key) = std::env::var("OPENAI_API_KEY") {
-                config.openai_api_key = Some(key);
+        if let Some(ProviderConfig::Openai { api_key, .. }) = config.provider_mut("openai") {
+            if api_key.is_none() {
+                if let Ok(key) = std::env::var("OPENAI_API_KEY") {
+                    *api_key = Some(key);
+                }
             }
         }
 
         config
     }
 
+    /// Rebuild a `Vec<ProviderConfig>` from the flat keys an older settings.conf used.
+    fn migrate_legacy_keys(ini: &Ini) -> Vec<ProviderConfig> {
+        let extra_headers: HashMap<String, String> = ini
+            .get("llm", "extra_headers")
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+
+        vec![
+            ProviderConfig::Groq {
+                api_key: ini.get("llm", "groq_api_key").filter(|k| !k.is_empty()),
+                model: ini.get("llm", "groq_model").unwrap_or_default(),
+                base_url: ini
+                    .get("llm", "groq_base_url")
+                    .filter(|u| !u.is_empty())
+                    .unwrap_or_else(|| DEFAULT_GROQ_BASE_URL.to_string()),
+                extra_headers: extra_headers.clone(),
+            },
+            ProviderConfig::Openai {
+                api_key: ini.get("llm", "openai_api_key").filter(|k| !k.is_empty()),
+                model: ini.get("llm", "openai_model").unwrap_or_default(),
+                base_url: ini
+                    .get("llm", "openai_base_url")
+                    .filter(|u| !u.is_empty())
+                    .unwrap_or_else(|| DEFAULT_OPENAI_BASE_URL.to_string()),
+                extra_headers,
+            },
+            ProviderConfig::Ollama {
+                base_url: ini
+                    .get("llm", "ollama_base_url")
+                    .unwrap_or_else(|| DEFAULT_OLLAMA_BASE_URL.to_string()),
+                model: ini.get("llm", "ollama_model").unwrap_or_default(),
+            },
+        ]
+    }
+
     /// Save LLM configuration to config file.
     pub fn save(&self) -> Result<(), String> {
         let config_path = Self::config_path()
@@ -98,14 +226,18 @@ impl LlmConfig {
             let _ = ini.load(&config_path);
         }
 
-        // Set [llm] section
-        ini.set("llm", "provider", Some(self.provider.clone()));
-        ini.set("llm", "groq_api_key", self.groq_api_key.clone());
-        ini.set("llm", "groq_model", Some(self.groq_model.clone()));
-        ini.set("llm", "openai_api_key", self.openai_api_key.clone());
-        ini.set("llm", "openai_model", Some(self.openai_model.clone()));
-        ini.set("llm", "ollama_base_url", Some(self.ollama_base_url.clone()));
-        ini.set("llm", "ollama_model", Some(self.ollama_model.clone()));
+        ini.set("llm", "provider", Some(self.active_provider.clone()));
+        ini.set(
+            "llm",
+            "providers",
+            Some(serde_json::to_string(&self.providers).unwrap_or_default()),
+        );
+        ini.set("llm", "system_message", self.default_system_message.clone());
+        ini.set(
+            "llm",
+            "presets",
+            Some(serde_json::to_string(&self.presets).unwrap_or_default()),
+        );
 
         ini.write(&config_path)
             .map_err(|e| format!("Failed to write config file: {}", e))?;
@@ -118,13 +250,119 @@ impl LlmConfig {
         dirs::config_dir().map(|d| d.join("ostk").join("settings.conf"))
     }
 
-    /// Check if the current provider is configured with API key.
+    /// The currently-selected provider's settings, if registered.
+    pub fn active(&self) -> Option<&ProviderConfig> {
+        self.provider(&self.active_provider)
+    }
+
+    /// Look up a provider's settings by registry key (`"groq"`, `"openai"`, `"ollama"`).
+    pub fn provider(&self, type_name: &str) -> Option<&ProviderConfig> {
+        self.providers.iter().find(|p| p.type_name() == type_name)
+    }
+
+    /// Mutable lookup, inserting a freshly-defaulted entry if `type_name` isn't registered yet.
+    pub fn provider_mut(&mut self, type_name: &str) -> Option<&mut ProviderConfig> {
+        if self.providers.iter().all(|p| p.type_name() != type_name) {
+            self.providers.push(ProviderConfig::default_for(type_name));
+        }
+        self.providers.iter_mut().find(|p| p.type_name() == type_name)
+    }
+
+    /// Check if the active provider is configured with whatever it needs.
     pub fn is_configured(&self) -> bool {
-        match self.provider.as_str() {
-            "groq" => self.groq_api_key.is_some(),
-            "openai" => self.openai_api_key.is_some(),
-            "ollama" => true, // Ollama doesn't need API key
-            _ => false,
+        self.active().map(ProviderConfig::is_configured).unwrap_or(false)
+    }
+
+    /// Look up a saved preset by name.
+    pub fn preset(&self, name: &str) -> Option<&AgentPreset> {
+        self.presets.iter().find(|p| p.name == name)
+    }
+}
+
+/// S3-compatible object storage configuration.
+#[derive(Debug, Clone, Default)]
+pub struct S3Config {
+    pub bucket: String,
+    pub region: String,
+    /// Custom endpoint URL, for self-hosted S3-compatible stores.
+    pub endpoint_url: Option<String>,
+    pub access_key: Option<String>,
+    pub secret_key: Option<String>,
+}
+
+impl S3Config {
+    /// Load S3 configuration from the shared config file.
+    pub fn load() -> Self {
+        let config_path = Self::config_path();
+        let mut config = S3Config::default();
+        config.region = "us-east-1".to_string();
+
+        if let Some(path) = config_path {
+            if path.exists() {
+                let mut ini = Ini::new();
+                if ini.load(&path).is_ok() {
+                    if let Some(bucket) = ini.get("s3", "bucket") {
+                        config.bucket = bucket;
+                    }
+                    if let Some(region) = ini.get("s3", "region") {
+                        config.region = region;
+                    }
+                    if let Some(endpoint) = ini.get("s3", "endpoint_url") {
+                        if !endpoint.is_empty() {
+                            config.endpoint_url = Some(endpoint);
+                        }
+                    }
+                    if let Some(key) = ini.get("s3", "access_key") {
+                        if !key.is_empty() {
+                            config.access_key = Some(key);
+                        }
+                    }
+                    if let Some(secret) = ini.get("s3", "secret_key") {
+                        if !secret.is_empty() {
+                            config.secret_key = Some(secret);
+                        }
+                    }
+                }
+            }
         }
+
+        config
+    }
+
+    /// Save S3 configuration to the shared config file, preserving other sections.
+    pub fn save(&self) -> Result<(), String> {
+        let config_path =
+            Self::config_path().ok_or("Could not determine config directory")?;
+
+        if let Some(parent) = config_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+
+        let mut ini = Ini::new();
+        if config_path.exists() {
+            let _ = ini.load(&config_path);
+        }
+
+        ini.set("s3", "bucket", Some(self.bucket.clone()));
+        ini.set("s3", "region", Some(self.region.clone()));
+        ini.set("s3", "endpoint_url", self.endpoint_url.clone());
+        ini.set("s3", "access_key", self.access_key.clone());
+        ini.set("s3", "secret_key", self.secret_key.clone());
+
+        ini.write(&config_path)
+            .map_err(|e| format!("Failed to write config file: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Same `settings.conf` file as [`LlmConfig`], under a different section.
+    fn config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("ostk").join("settings.conf"))
+    }
+
+    /// Check if enough credentials are present to attempt an upload.
+    pub fn is_configured(&self) -> bool {
+        !self.bucket.is_empty() && self.access_key.is_some() && self.secret_key.is_some()
     }
 }
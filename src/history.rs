@@ -0,0 +1,229 @@
+//! Persistent, full-text searchable index of executed queries.
+
+use opensky::QueryParams;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Schema, FAST, STORED, STRING, TEXT};
+use tantivy::{doc, Index, IndexWriter, ReloadPolicy};
+
+use crate::agent::QueryType;
+
+/// A single recorded execution, ready to hand back to the frontend.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HistoryEntry {
+    pub query_type: String,
+    pub sql: String,
+    pub params: Value,
+    pub row_count: u64,
+    pub timestamp: i64,
+}
+
+/// Tantivy-backed index of past query executions.
+pub struct QueryHistoryIndex {
+    index: Index,
+    sql_field: tantivy::schema::Field,
+    search_field: tantivy::schema::Field,
+    query_type_field: tantivy::schema::Field,
+    params_field: tantivy::schema::Field,
+    timestamp_field: tantivy::schema::Field,
+    row_count_field: tantivy::schema::Field,
+}
+
+impl QueryHistoryIndex {
+    /// Open the on-disk index under the app config dir, creating it on first run.
+    ///
+    /// History is a non-essential add-on, so a corrupt index, a stale writer
+    /// lock, or a schema mismatch must never take down the whole app: we
+    /// retry once against a freshly recreated directory, and failing that
+    /// fall back to an in-memory index (history just won't persist across
+    /// restarts for this session).
+    pub fn open_or_create() -> Self {
+        let index_dir = Self::index_dir();
+        std::fs::create_dir_all(&index_dir).ok();
+
+        Self::open_or_create_in_dir(&index_dir)
+            .and_then(Self::from_index)
+            .or_else(|e| {
+                eprintln!(
+                    "query history index at {:?} is unusable ({}), rebuilding it",
+                    index_dir, e
+                );
+                std::fs::remove_dir_all(&index_dir).ok();
+                std::fs::create_dir_all(&index_dir).ok();
+                Self::open_or_create_in_dir(&index_dir).and_then(Self::from_index)
+            })
+            .unwrap_or_else(|e| {
+                eprintln!(
+                    "failed to rebuild query history index ({}), disabling persistence for this session",
+                    e
+                );
+                Self::from_index(Index::create_in_ram(Self::schema()))
+                    .expect("freshly created in-ram index always matches Self::schema()")
+            })
+    }
+
+    fn schema() -> Schema {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("sql", TEXT | STORED);
+        // Searchable-only: SQL plus the callsign/airport identifiers folded
+        // in, so a query for a callsign or airport surfaces the run even
+        // without matching the literal SQL. Never handed back to the
+        // frontend, so it doesn't need STORED.
+        schema_builder.add_text_field("search", TEXT);
+        schema_builder.add_text_field("query_type", STRING | STORED);
+        schema_builder.add_text_field("params", STORED);
+        schema_builder.add_i64_field("timestamp", FAST | STORED);
+        schema_builder.add_u64_field("row_count", FAST | STORED);
+        schema_builder.build()
+    }
+
+    fn open_or_create_in_dir(index_dir: &PathBuf) -> tantivy::Result<Index> {
+        if tantivy::directory::MmapDirectory::open(index_dir)
+            .map(|dir| Index::exists(&dir).unwrap_or(false))
+            .unwrap_or(false)
+        {
+            Index::open_in_dir(index_dir)
+        } else {
+            Index::create_in_dir(index_dir, Self::schema())
+        }
+    }
+
+    fn from_index(index: Index) -> tantivy::Result<Self> {
+        let schema = index.schema();
+        let missing_field = |name: &str| {
+            tantivy::TantivyError::SchemaError(format!("schema has no '{}' field", name))
+        };
+        Ok(Self {
+            sql_field: schema.get_field("sql").map_err(|_| missing_field("sql"))?,
+            search_field: schema
+                .get_field("search")
+                .map_err(|_| missing_field("search"))?,
+            query_type_field: schema
+                .get_field("query_type")
+                .map_err(|_| missing_field("query_type"))?,
+            params_field: schema
+                .get_field("params")
+                .map_err(|_| missing_field("params"))?,
+            timestamp_field: schema
+                .get_field("timestamp")
+                .map_err(|_| missing_field("timestamp"))?,
+            row_count_field: schema
+                .get_field("row_count")
+                .map_err(|_| missing_field("row_count"))?,
+            index,
+        })
+    }
+
+    fn index_dir() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("ostk")
+            .join("query_history")
+    }
+
+    /// Record a completed query execution.
+    pub fn add_entry(
+        &self,
+        query_type: QueryType,
+        sql: &str,
+        params: &QueryParams,
+        row_count: usize,
+        timestamp: i64,
+    ) -> tantivy::Result<()> {
+        let mut writer: IndexWriter = self.index.writer(15_000_000)?;
+
+        let params_json = json!({
+            "icao24": params.icao24,
+            "start": params.start,
+            "stop": params.stop,
+            "callsign": params.callsign,
+            "departure_airport": params.departure_airport,
+            "arrival_airport": params.arrival_airport,
+            "airport": params.airport,
+            "limit": params.limit,
+        });
+
+        // Fold the searchable identifiers into a search-only field so a query
+        // for a callsign or airport surfaces the run even without matching
+        // the literal SQL. `sql_field` keeps the clean SQL text for display.
+        let searchable = format!(
+            "{} {} {} {} {}",
+            sql,
+            params.callsign.as_deref().unwrap_or(""),
+            params.departure_airport.as_deref().unwrap_or(""),
+            params.arrival_airport.as_deref().unwrap_or(""),
+            params.airport.as_deref().unwrap_or(""),
+        );
+
+        writer.add_document(doc!(
+            self.sql_field => sql,
+            self.search_field => searchable,
+            self.query_type_field => query_type.to_string(),
+            self.params_field => params_json.to_string(),
+            self.timestamp_field => timestamp,
+            self.row_count_field => row_count as u64,
+        ))?;
+
+        writer.commit()?;
+        Ok(())
+    }
+
+    /// Free-text search over recorded executions. Query terms match indexed
+    /// tokens exactly; there is no fuzzy (edit-distance) matching. The top
+    /// `limit` hits by relevance are returned, ordered most recent first.
+    pub fn search(&self, query: &str, limit: u32) -> tantivy::Result<Vec<HistoryEntry>> {
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
+        let searcher = reader.searcher();
+
+        let query_parser =
+            QueryParser::for_index(&self.index, vec![self.sql_field, self.search_field]);
+        let parsed_query = query_parser.parse_query(query)?;
+
+        // `TopDocs::with_limit` panics on 0, so floor the IPC-supplied limit at 1.
+        let top_docs = searcher.search(&parsed_query, &TopDocs::with_limit((limit as usize).max(1)))?;
+
+        let mut entries = Vec::with_capacity(top_docs.len());
+        for (_score, doc_address) in top_docs {
+            let retrieved = searcher.doc(doc_address)?;
+            entries.push(self.entry_from_doc(&retrieved));
+        }
+
+        entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(entries)
+    }
+
+    fn entry_from_doc(&self, retrieved: &tantivy::TantivyDocument) -> HistoryEntry {
+        use tantivy::schema::document::Value as _;
+
+        let text_field = |field| -> String {
+            retrieved
+                .get_first(field)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let params: Value = serde_json::from_str(&text_field(self.params_field))
+            .unwrap_or_else(|_| json!({}));
+
+        HistoryEntry {
+            query_type: text_field(self.query_type_field),
+            sql: text_field(self.sql_field),
+            params,
+            row_count: retrieved
+                .get_first(self.row_count_field)
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0),
+            timestamp: retrieved
+                .get_first(self.timestamp_field)
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0),
+        }
+    }
+}
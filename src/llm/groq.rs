@@ -1,129 +1,82 @@
 //! Groq API client for LLM completions.
+//!
+//! Groq speaks the same wire format as OpenAI, so this is just
+//! `OpenAiCompatClient` pointed at the Groq endpoint.
 
-use super::{CompletionOptions, LlmError, Message};
-use serde::{Deserialize, Serialize};
+use super::openai_compat::{self, OpenAiCompatClient};
+use super::{CompletionOptions, CompletionResult, LlmError, LlmProvider, Message, ToolDefinition};
+use futures_util::Stream;
+use std::pin::Pin;
 
-const GROQ_API_URL: &str = "https://api.groq.com/openai/v1/chat/completions";
+const GROQ_MODELS_URL: &str = "https://api.groq.com/openai/v1/models";
 
 /// Groq API client.
 #[derive(Debug, Clone)]
 pub struct GroqClient {
-    api_key: String,
-    model: String,
-    client: reqwest::Client,
-}
-
-#[derive(Debug, Serialize)]
-struct ChatRequest {
-    model: String,
-    messages: Vec<Message>,
-    temperature: f32,
-    max_tokens: u32,
-}
-
-#[derive(Debug, Deserialize)]
-struct ChatResponse {
-    choices: Vec<Choice>,
-}
-
-#[derive(Debug, Deserialize)]
-struct Choice {
-    message: ResponseMessage,
-}
-
-#[derive(Debug, Deserialize)]
-struct ResponseMessage {
-    content: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: ErrorDetail,
-}
-
-#[derive(Debug, Deserialize)]
-struct ErrorDetail {
-    message: String,
+    inner: OpenAiCompatClient,
 }
 
 impl GroqClient {
-    /// Create a new Groq client.
-    pub fn new(api_key: impl Into<String>, model: impl Into<String>) -> Self {
+    /// Create a new Groq client against `base_url` (the Groq endpoint by
+    /// default, or a proxy/gateway that speaks the same wire format).
+    pub fn new(base_url: impl Into<String>, api_key: impl Into<String>, model: impl Into<String>) -> Self {
         Self {
-            api_key: api_key.into(),
-            model: model.into(),
-            client: reqwest::Client::new(),
+            inner: OpenAiCompatClient::new(base_url, Some(api_key.into()), model),
         }
     }
 
+    /// Attach extra headers (e.g. proxy auth) sent with every request.
+    pub fn with_extra_headers(mut self, extra_headers: std::collections::HashMap<String, String>) -> Self {
+        self.inner = self.inner.with_extra_headers(extra_headers);
+        self
+    }
+
     /// Send a chat completion request.
     pub async fn complete(
         &self,
         messages: Vec<Message>,
         options: CompletionOptions,
     ) -> Result<String, LlmError> {
-        let request = ChatRequest {
-            model: self.model.clone(),
-            messages,
-            temperature: options.temperature,
-            max_tokens: options.max_tokens,
-        };
-
-        let response = self
-            .client
-            .post(GROQ_API_URL)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error: ErrorResponse = response.json().await?;
-            return Err(LlmError::Api(error.error.message));
-        }
-
-        let chat_response: ChatResponse = response.json().await?;
+        self.inner.complete(messages, options).await
+    }
 
-        chat_response
-            .choices
-            .first()
-            .map(|c| c.message.content.clone())
-            .ok_or_else(|| LlmError::Api("No response from model".to_string()))
+    /// Send a chat completion request, streaming content deltas as they arrive.
+    pub fn complete_stream(
+        &self,
+        messages: Vec<Message>,
+        options: CompletionOptions,
+    ) -> Pin<Box<dyn Stream<Item = Result<String, LlmError>> + Send>> {
+        self.inner.complete_stream(messages, options)
     }
 
     /// Get the model name.
-    #[allow(dead_code)]  // Used by Agent::model()
+    #[allow(dead_code)] // Agent::model() goes through the LlmProvider impl instead
     pub fn model(&self) -> &str {
-        &self.model
+        self.inner.model()
     }
 }
 
-/// Fetch available models from Groq API.
-pub async fn fetch_models(api_key: &str) -> Result<Vec<String>, LlmError> {
-    let client = reqwest::Client::new();
-
-    let response = client
-        .get("https://api.groq.com/openai/v1/models")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        let error: ErrorResponse = response.json().await?;
-        return Err(LlmError::Api(error.error.message));
+#[async_trait::async_trait]
+impl LlmProvider for GroqClient {
+    async fn complete(&self, messages: Vec<Message>, options: CompletionOptions) -> Result<String, LlmError> {
+        self.inner.complete(messages, options).await
     }
 
-    #[derive(Deserialize)]
-    struct ModelsResponse {
-        data: Vec<ModelInfo>,
+    async fn complete_with_tool(
+        &self,
+        messages: Vec<Message>,
+        options: CompletionOptions,
+        tool: &ToolDefinition,
+    ) -> Result<CompletionResult, LlmError> {
+        self.inner.complete_with_tool(messages, options, tool).await
     }
 
-    #[derive(Deserialize)]
-    struct ModelInfo {
-        id: String,
+    fn model(&self) -> &str {
+        self.inner.model()
     }
+}
 
-    let models: ModelsResponse = response.json().await?;
-    Ok(models.data.into_iter().map(|m| m.id).collect())
+/// Fetch available models from Groq API.
+pub async fn fetch_models(api_key: &str) -> Result<Vec<String>, LlmError> {
+    openai_compat::fetch_models(GROQ_MODELS_URL, api_key).await
 }
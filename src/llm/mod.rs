@@ -1,8 +1,13 @@
 //! LLM provider integrations for OSTK.
 
 mod groq;
+mod ollama;
+mod openai;
+mod openai_compat;
 
-pub use groq::{fetch_models, GroqClient};
+pub use groq::GroqClient;
+pub use ollama::OllamaClient;
+pub use openai::OpenAiClient;
 
 use serde::{Deserialize, Serialize};
 
@@ -45,6 +50,22 @@ impl Default for CompletionOptions {
     }
 }
 
+/// A function-calling tool definition sent to providers that support it.
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    /// JSON schema describing the tool's arguments.
+    pub parameters: serde_json::Value,
+}
+
+/// Outcome of a tool-calling completion.
+pub enum CompletionResult {
+    /// The model invoked the requested tool; these are its raw JSON arguments.
+    ToolCall(String),
+    /// The model replied with plain text (e.g. the JSON-object-mode fallback).
+    Text(String),
+}
+
 /// Error type for LLM operations.
 #[derive(Debug, thiserror::Error)]
 #[allow(dead_code)]  // Some variants reserved for future use
@@ -61,3 +82,105 @@ pub enum LlmError {
     #[error("Parse error: {0}")]
     Parse(String),
 }
+
+/// Shared interface implemented by every LLM backend (Groq, OpenAI, Ollama, ...).
+///
+/// This lets `Agent` hold any backend behind a single type, instead of being
+/// hard-wired to one concrete client.
+#[async_trait::async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Send a chat completion request and return the model's reply text.
+    async fn complete(&self, messages: Vec<Message>, options: CompletionOptions) -> Result<String, LlmError>;
+
+    /// Like `complete`, but asks the model to call `tool` and returns its
+    /// arguments directly instead of free-form text. Providers without
+    /// function-calling support can rely on this default, which appends the
+    /// tool's schema as an instruction so a plain completion still has a
+    /// shot at returning parseable JSON, and reports it as
+    /// `CompletionResult::Text`.
+    async fn complete_with_tool(
+        &self,
+        messages: Vec<Message>,
+        options: CompletionOptions,
+        tool: &ToolDefinition,
+    ) -> Result<CompletionResult, LlmError> {
+        let mut messages = messages;
+        messages.push(Message::system(format!(
+            "Respond with ONLY a JSON object (no prose, no markdown fences) matching this schema:\n{}",
+            tool.parameters
+        )));
+        self.complete(messages, options).await.map(CompletionResult::Text)
+    }
+
+    /// The model name this client is currently configured to use.
+    fn model(&self) -> &str;
+}
+
+/// Dispatch a model listing request to the right backend for `provider`.
+pub async fn fetch_models(
+    provider: &str,
+    api_key: Option<String>,
+    base_url: Option<String>,
+) -> Result<Vec<String>, LlmError> {
+    match provider {
+        "groq" => {
+            let key = api_key.ok_or_else(|| LlmError::NotConfigured("Groq API key".to_string()))?;
+            groq::fetch_models(&key).await
+        }
+        "openai" => {
+            let key = api_key.ok_or_else(|| LlmError::NotConfigured("OpenAI API key".to_string()))?;
+            openai::fetch_models(&key).await
+        }
+        "ollama" => {
+            let base_url = base_url.unwrap_or_else(|| "http://localhost:11434".to_string());
+            ollama::fetch_models(&base_url).await
+        }
+        other => Err(LlmError::NotConfigured(format!("Unknown provider: {}", other))),
+    }
+}
+
+/// Wires a `ProviderConfig` variant to the client constructor `init` should
+/// use for it, so `init` itself grows by one macro arm instead of a
+/// hand-written match. The `ProviderConfig` enum and the per-provider
+/// matches in `config.rs` and `commands.rs` still take a hand-written arm
+/// per provider - their fields and JSON shape aren't uniform enough across
+/// providers (Ollama has neither `api_key` nor `extra_headers`) to generate.
+macro_rules! register_provider {
+    ($(($variant:ident, $build:expr)),+ $(,)?) => {
+        /// Build the concrete client for `config`'s active provider, or
+        /// `None` if it isn't configured.
+        pub fn init(config: &crate::config::LlmConfig) -> Option<Box<dyn LlmProvider>> {
+            match config.active()? {
+                $(
+                    crate::config::ProviderConfig::$variant { .. } => {
+                        let build: fn(&crate::config::ProviderConfig) -> Option<Box<dyn LlmProvider>> = $build;
+                        build(config.active()?)
+                    }
+                )+
+            }
+        }
+    };
+}
+
+register_provider!(
+    (Groq, |c| {
+        let crate::config::ProviderConfig::Groq { api_key, model, base_url, extra_headers } = c else {
+            return None;
+        };
+        let client = GroqClient::new(base_url, api_key.clone()?, model).with_extra_headers(extra_headers.clone());
+        Some(Box::new(client) as Box<dyn LlmProvider>)
+    }),
+    (Openai, |c| {
+        let crate::config::ProviderConfig::Openai { api_key, model, base_url, extra_headers } = c else {
+            return None;
+        };
+        let client = OpenAiClient::new(base_url, api_key.clone()?, model).with_extra_headers(extra_headers.clone());
+        Some(Box::new(client) as Box<dyn LlmProvider>)
+    }),
+    (Ollama, |c| {
+        let crate::config::ProviderConfig::Ollama { base_url, model } = c else {
+            return None;
+        };
+        Some(Box::new(OllamaClient::new(base_url, model)) as Box<dyn LlmProvider>)
+    }),
+);
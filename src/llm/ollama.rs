@@ -0,0 +1,148 @@
+//! Ollama API client for local LLM completions.
+
+use super::{CompletionOptions, CompletionResult, LlmError, LlmProvider, Message, ToolDefinition};
+use serde::{Deserialize, Serialize};
+
+/// Ollama API client, pointed at a configurable local (or remote) base URL.
+#[derive(Debug, Clone)]
+pub struct OllamaClient {
+    base_url: String,
+    model: String,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<Message>,
+    stream: bool,
+    options: ChatOptions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatOptions {
+    temperature: f32,
+    num_predict: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    message: ResponseMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseMessage {
+    content: String,
+}
+
+impl OllamaClient {
+    /// Create a new Ollama client against `base_url` (e.g. `http://localhost:11434`).
+    pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            model: model.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Send a chat completion request.
+    pub async fn complete(
+        &self,
+        messages: Vec<Message>,
+        options: CompletionOptions,
+    ) -> Result<String, LlmError> {
+        self.complete_with_format(messages, options, None).await
+    }
+
+    /// Like `complete`, but `format` can request Ollama's native JSON mode
+    /// (`Some("json")`) instead of hoping the model's prose happens to be
+    /// valid JSON.
+    async fn complete_with_format(
+        &self,
+        messages: Vec<Message>,
+        options: CompletionOptions,
+        format: Option<&str>,
+    ) -> Result<String, LlmError> {
+        let request = ChatRequest {
+            model: self.model.clone(),
+            messages,
+            stream: false,
+            options: ChatOptions {
+                temperature: options.temperature,
+                num_predict: options.max_tokens,
+            },
+            format: format.map(str::to_string),
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(LlmError::Api(text));
+        }
+
+        let chat_response: ChatResponse = response.json().await?;
+        Ok(chat_response.message.content)
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmProvider for OllamaClient {
+    async fn complete(&self, messages: Vec<Message>, options: CompletionOptions) -> Result<String, LlmError> {
+        self.complete(messages, options).await
+    }
+
+    /// Ollama has no function-calling API, so we fall back to its native
+    /// JSON mode: ask for `format: "json"` and append the tool's schema as
+    /// an instruction so the model's free-form JSON actually matches it.
+    async fn complete_with_tool(
+        &self,
+        messages: Vec<Message>,
+        options: CompletionOptions,
+        tool: &ToolDefinition,
+    ) -> Result<CompletionResult, LlmError> {
+        let mut messages = messages;
+        messages.push(Message::system(format!(
+            "Respond with ONLY a JSON object (no prose, no markdown fences) matching this schema:\n{}",
+            tool.parameters
+        )));
+        let text = self.complete_with_format(messages, options, Some("json")).await?;
+        Ok(CompletionResult::Text(text))
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+}
+
+/// Fetch models currently pulled into a local/remote Ollama instance.
+pub async fn fetch_models(base_url: &str) -> Result<Vec<String>, LlmError> {
+    let client = reqwest::Client::new();
+
+    let response = client.get(format!("{}/api/tags", base_url)).send().await?;
+
+    if !response.status().is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(LlmError::Api(text));
+    }
+
+    #[derive(Deserialize)]
+    struct TagsResponse {
+        models: Vec<ModelInfo>,
+    }
+
+    #[derive(Deserialize)]
+    struct ModelInfo {
+        name: String,
+    }
+
+    let tags: TagsResponse = response.json().await?;
+    Ok(tags.models.into_iter().map(|m| m.name).collect())
+}
@@ -0,0 +1,77 @@
+//! OpenAI API client for LLM completions.
+
+use super::openai_compat::{self, OpenAiCompatClient};
+use super::{CompletionOptions, CompletionResult, LlmError, LlmProvider, Message, ToolDefinition};
+use futures_util::Stream;
+use std::pin::Pin;
+
+const OPENAI_MODELS_URL: &str = "https://api.openai.com/v1/models";
+
+/// OpenAI API client.
+#[derive(Debug, Clone)]
+pub struct OpenAiClient {
+    inner: OpenAiCompatClient,
+}
+
+impl OpenAiClient {
+    /// Create a new OpenAI client against `base_url` (the OpenAI endpoint by
+    /// default, or Azure OpenAI / OpenRouter / a self-hosted gateway).
+    pub fn new(base_url: impl Into<String>, api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            inner: OpenAiCompatClient::new(base_url, Some(api_key.into()), model),
+        }
+    }
+
+    /// Attach extra headers (e.g. `OpenAI-Organization`, proxy auth) sent
+    /// with every request.
+    pub fn with_extra_headers(mut self, extra_headers: std::collections::HashMap<String, String>) -> Self {
+        self.inner = self.inner.with_extra_headers(extra_headers);
+        self
+    }
+
+    /// Send a chat completion request.
+    pub async fn complete(
+        &self,
+        messages: Vec<Message>,
+        options: CompletionOptions,
+    ) -> Result<String, LlmError> {
+        self.inner.complete(messages, options).await
+    }
+
+    /// Send a chat completion request, streaming content deltas as they arrive.
+    pub fn complete_stream(
+        &self,
+        messages: Vec<Message>,
+        options: CompletionOptions,
+    ) -> Pin<Box<dyn Stream<Item = Result<String, LlmError>> + Send>> {
+        self.inner.complete_stream(messages, options)
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmProvider for OpenAiClient {
+    async fn complete(&self, messages: Vec<Message>, options: CompletionOptions) -> Result<String, LlmError> {
+        self.inner.complete(messages, options).await
+    }
+
+    async fn complete_with_tool(
+        &self,
+        messages: Vec<Message>,
+        options: CompletionOptions,
+        tool: &ToolDefinition,
+    ) -> Result<CompletionResult, LlmError> {
+        self.inner.complete_with_tool(messages, options, tool).await
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+}
+
+/// Fetch available chat models from OpenAI.
+pub async fn fetch_models(api_key: &str) -> Result<Vec<String>, LlmError> {
+    let models = openai_compat::fetch_models(OPENAI_MODELS_URL, api_key).await?;
+    // The models endpoint lists every model (embeddings, TTS, ...); keep
+    // this to the chat-completion-capable ones users would pick from.
+    Ok(models.into_iter().filter(|id| id.contains("gpt")).collect())
+}
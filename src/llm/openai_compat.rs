@@ -0,0 +1,329 @@
+//! Shared request/response wire format for OpenAI-style chat completion APIs.
+//!
+//! Groq and OpenAI both speak the same `/chat/completions` JSON shape, so a
+//! single client parameterized by base URL and an optional API key covers
+//! both - Groq is just an instance pointed at the Groq endpoint.
+
+use super::{CompletionOptions, CompletionResult, LlmError, Message, ToolDefinition};
+use futures_util::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::pin::Pin;
+
+#[derive(Debug, Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<Message>,
+    temperature: f32,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChunk {
+    choices: Vec<StreamChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChoice {
+    delta: StreamDelta,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct StreamDelta {
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    message: ResponseMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseMessage {
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolCall {
+    function: FunctionCall,
+}
+
+#[derive(Debug, Deserialize)]
+struct FunctionCall {
+    arguments: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    error: ErrorDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorDetail {
+    message: String,
+}
+
+/// Client for any OpenAI-wire-compatible `/chat/completions` endpoint.
+#[derive(Debug, Clone)]
+pub struct OpenAiCompatClient {
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+    extra_headers: std::collections::HashMap<String, String>,
+    client: reqwest::Client,
+}
+
+impl OpenAiCompatClient {
+    /// `base_url` is the full completions endpoint (e.g.
+    /// `https://api.groq.com/openai/v1/chat/completions`). `api_key` is
+    /// optional since some self-hosted gateways don't require one.
+    pub fn new(
+        base_url: impl Into<String>,
+        api_key: Option<String>,
+        model: impl Into<String>,
+    ) -> Self {
+        Self {
+            base_url: base_url.into(),
+            api_key,
+            model: model.into(),
+            extra_headers: std::collections::HashMap::new(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Attach extra headers (e.g. `OpenAI-Organization`, proxy auth) sent
+    /// with every request.
+    pub fn with_extra_headers(mut self, extra_headers: std::collections::HashMap<String, String>) -> Self {
+        self.extra_headers = extra_headers;
+        self
+    }
+
+    pub async fn complete(
+        &self,
+        messages: Vec<Message>,
+        options: CompletionOptions,
+    ) -> Result<String, LlmError> {
+        let request = ChatRequest {
+            model: self.model.clone(),
+            messages,
+            temperature: options.temperature,
+            max_tokens: options.max_tokens,
+            stream: false,
+            tools: None,
+            tool_choice: None,
+            response_format: None,
+        };
+
+        let message = self.send_chat(request).await?;
+        message
+            .content
+            .ok_or_else(|| LlmError::Api("No response from model".to_string()))
+    }
+
+    /// Ask the model to call `tool` rather than reply in prose. Models that
+    /// ignore `tool_choice` (no function-calling support) get retried once
+    /// in JSON-object mode instead of erroring.
+    pub async fn complete_with_tool(
+        &self,
+        messages: Vec<Message>,
+        options: CompletionOptions,
+        tool: &ToolDefinition,
+    ) -> Result<CompletionResult, LlmError> {
+        let request = ChatRequest {
+            model: self.model.clone(),
+            messages: messages.clone(),
+            temperature: options.temperature,
+            max_tokens: options.max_tokens,
+            stream: false,
+            tools: Some(vec![json!({
+                "type": "function",
+                "function": {
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": tool.parameters,
+                },
+            })]),
+            tool_choice: Some(json!({
+                "type": "function",
+                "function": {"name": tool.name},
+            })),
+            response_format: None,
+        };
+
+        let message = self.send_chat(request).await?;
+        if let Some(call) = message.tool_calls.into_iter().next() {
+            return Ok(CompletionResult::ToolCall(call.function.arguments));
+        }
+
+        let fallback = ChatRequest {
+            model: self.model.clone(),
+            messages,
+            temperature: options.temperature,
+            max_tokens: options.max_tokens,
+            stream: false,
+            tools: None,
+            tool_choice: None,
+            response_format: Some(json!({"type": "json_object"})),
+        };
+        let message = self.send_chat(fallback).await?;
+        Ok(CompletionResult::Text(message.content.unwrap_or_default()))
+    }
+
+    async fn send_chat(&self, request: ChatRequest) -> Result<ResponseMessage, LlmError> {
+        let mut req = self.client.post(&self.base_url).json(&request);
+        if let Some(api_key) = &self.api_key {
+            req = req.header("Authorization", format!("Bearer {}", api_key));
+        }
+        for (name, value) in &self.extra_headers {
+            req = req.header(name, value);
+        }
+
+        let response = req.send().await?;
+
+        if !response.status().is_success() {
+            let error: ErrorResponse = response.json().await?;
+            return Err(LlmError::Api(error.error.message));
+        }
+
+        let mut chat_response: ChatResponse = response.json().await?;
+        if chat_response.choices.is_empty() {
+            return Err(LlmError::Api("No response from model".to_string()));
+        }
+        Ok(chat_response.choices.remove(0).message)
+    }
+
+    /// Like `complete`, but yields content deltas as they stream in over SSE
+    /// instead of waiting for the full response.
+    pub fn complete_stream(
+        &self,
+        messages: Vec<Message>,
+        options: CompletionOptions,
+    ) -> Pin<Box<dyn Stream<Item = Result<String, LlmError>> + Send>> {
+        let client = self.client.clone();
+        let base_url = self.base_url.clone();
+        let api_key = self.api_key.clone();
+        let extra_headers = self.extra_headers.clone();
+        let model = self.model.clone();
+
+        Box::pin(async_stream::stream! {
+            let request = ChatRequest {
+                model,
+                messages,
+                temperature: options.temperature,
+                max_tokens: options.max_tokens,
+                stream: true,
+                tools: None,
+                tool_choice: None,
+                response_format: None,
+            };
+
+            let mut req = client.post(&base_url).json(&request);
+            if let Some(api_key) = &api_key {
+                req = req.header("Authorization", format!("Bearer {}", api_key));
+            }
+            for (name, value) in &extra_headers {
+                req = req.header(name, value);
+            }
+
+            let response = match req.send().await {
+                Ok(r) => r,
+                Err(e) => {
+                    yield Err(LlmError::Http(e));
+                    return;
+                }
+            };
+
+            if !response.status().is_success() {
+                let text = response.text().await.unwrap_or_default();
+                yield Err(LlmError::Api(text));
+                return;
+            }
+
+            // SSE frames are separated by a blank line; a frame can arrive
+            // split across multiple TCP reads, so buffer until we see one.
+            let mut buffer = String::new();
+            let mut bytes = response.bytes_stream();
+
+            while let Some(chunk) = bytes.next().await {
+                let chunk = match chunk {
+                    Ok(c) => c,
+                    Err(e) => {
+                        yield Err(LlmError::Http(e));
+                        return;
+                    }
+                };
+                buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+                while let Some(frame_end) = buffer.find("\n\n") {
+                    let frame: String = buffer.drain(..frame_end + 2).collect();
+
+                    for line in frame.lines() {
+                        let Some(data) = line.strip_prefix("data: ") else { continue };
+                        if data == "[DONE]" {
+                            return;
+                        }
+
+                        match serde_json::from_str::<StreamChunk>(data) {
+                            Ok(parsed) => {
+                                if let Some(content) =
+                                    parsed.choices.first().and_then(|c| c.delta.content.clone())
+                                {
+                                    yield Ok(content);
+                                }
+                            }
+                            Err(e) => yield Err(LlmError::Parse(e.to_string())),
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+}
+
+/// List models from an OpenAI-compatible `/models` endpoint.
+pub async fn fetch_models(models_url: &str, api_key: &str) -> Result<Vec<String>, LlmError> {
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(models_url)
+        .header("Authorization", format!("Bearer {}", api_key))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let error: ErrorResponse = response.json().await?;
+        return Err(LlmError::Api(error.error.message));
+    }
+
+    #[derive(Deserialize)]
+    struct ModelsResponse {
+        data: Vec<ModelInfo>,
+    }
+
+    #[derive(Deserialize)]
+    struct ModelInfo {
+        id: String,
+    }
+
+    let models: ModelsResponse = response.json().await?;
+    Ok(models.data.into_iter().map(|m| m.id).collect())
+}
@@ -5,9 +5,12 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod agent;
+mod analytics;
 mod commands;
 mod config;
+mod history;
 mod llm;
+mod s3;
 mod state;
 
 use state::new_shared_state;
@@ -65,20 +68,40 @@ fn main() {
             commands::execute_query_async,
             commands::get_execution_status,
             commands::cancel_query,
+            // Query queue commands
+            commands::enqueue_query,
+            commands::get_queue_status,
+            commands::cancel_queued_job,
+            commands::clear_queue,
+            commands::load_queued_result,
             // Export commands
             commands::export_csv,
             commands::export_parquet,
+            commands::export_to_s3,
+            commands::get_s3_presigned_url,
+            commands::get_s3_config,
+            commands::save_s3_config,
+            // Analytics commands
+            commands::filter_result,
+            commands::aggregate_result,
             // Chat commands
             commands::get_messages,
             commands::clear_messages,
             commands::send_message,
+            commands::stream_chat_completion,
             // Config commands
             commands::get_opensky_config,
             commands::save_opensky_config,
             commands::get_llm_config,
             commands::save_llm_config,
+            commands::save_system_message,
+            commands::list_agent_presets,
+            commands::save_agent_preset,
+            commands::delete_agent_preset,
             commands::get_agent_status,
-            commands::fetch_groq_models,
+            commands::fetch_models,
+            // Query history commands
+            commands::search_query_history,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
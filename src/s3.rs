@@ -0,0 +1,83 @@
+//! Upload of exported query results to S3-compatible object storage.
+
+use crate::config::S3Config;
+use aws_sdk_s3::config::{Credentials, Region};
+use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::Client;
+use std::time::Duration;
+
+/// Error type for S3 operations.
+#[derive(Debug, thiserror::Error)]
+pub enum S3Error {
+    #[error("S3 is not configured: {0}")]
+    NotConfigured(String),
+
+    #[error("S3 upload error: {0}")]
+    Upload(String),
+
+    #[error("S3 presign error: {0}")]
+    Presign(String),
+}
+
+fn build_client(config: &S3Config) -> Result<Client, S3Error> {
+    let access_key = config
+        .access_key
+        .clone()
+        .ok_or_else(|| S3Error::NotConfigured("missing access key".to_string()))?;
+    let secret_key = config
+        .secret_key
+        .clone()
+        .ok_or_else(|| S3Error::NotConfigured("missing secret key".to_string()))?;
+
+    let credentials = Credentials::new(access_key, secret_key, None, None, "ostk");
+
+    let mut builder = aws_sdk_s3::config::Builder::new()
+        .region(Region::new(config.region.clone()))
+        .credentials_provider(credentials);
+
+    if let Some(endpoint) = &config.endpoint_url {
+        // Self-hosted stores (MinIO, etc.) need path-style addressing.
+        builder = builder.endpoint_url(endpoint).force_path_style(true);
+    }
+
+    Ok(Client::from_conf(builder.build()))
+}
+
+/// Upload `bytes` to `key` in the configured bucket. Returns the object key.
+pub async fn upload_object(config: &S3Config, key: &str, bytes: Vec<u8>) -> Result<String, S3Error> {
+    let client = build_client(config)?;
+
+    client
+        .put_object()
+        .bucket(&config.bucket)
+        .key(key)
+        .body(ByteStream::from(bytes))
+        .send()
+        .await
+        .map_err(|e| S3Error::Upload(e.to_string()))?;
+
+    Ok(key.to_string())
+}
+
+/// Generate a time-limited presigned GET URL for an already-uploaded object.
+pub async fn presigned_get_url(
+    config: &S3Config,
+    key: &str,
+    expires_in: Duration,
+) -> Result<String, S3Error> {
+    let client = build_client(config)?;
+
+    let presigning_config = PresigningConfig::expires_in(expires_in)
+        .map_err(|e| S3Error::Presign(e.to_string()))?;
+
+    let presigned = client
+        .get_object()
+        .bucket(&config.bucket)
+        .key(key)
+        .presigned(presigning_config)
+        .await
+        .map_err(|e| S3Error::Presign(e.to_string()))?;
+
+    Ok(presigned.uri().to_string())
+}
@@ -1,6 +1,9 @@
 //! Application state management for OSTK.
 
+use crate::agent::{AgentState, QueryType};
+use crate::history::QueryHistoryIndex;
 use opensky::{FlightData, QueryParams};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -11,6 +14,9 @@ pub struct ChatMessage {
     pub content: String,
     #[serde(rename = "type")]
     pub msg_type: String,
+    /// User-friendly hint shown alongside a "code" message, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
 }
 
 impl ChatMessage {
@@ -19,6 +25,21 @@ impl ChatMessage {
             role: role.into(),
             content: content.into(),
             msg_type: msg_type.into(),
+            hint: None,
+        }
+    }
+
+    pub fn with_hint(
+        role: impl Into<String>,
+        content: impl Into<String>,
+        msg_type: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Self {
+            role: role.into(),
+            content: content.into(),
+            msg_type: msg_type.into(),
+            hint: Some(hint.into()),
         }
     }
 }
@@ -55,11 +76,48 @@ pub enum ExecutionResult {
     Cancelled { cancelled: bool },
 }
 
+/// State of a job sitting in the query queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Pending,
+    Running,
+    Done,
+    Error,
+}
+
+/// A single snapshot of params queued for sequential execution.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct QueuedJob {
+    pub id: u64,
+    pub query_params: QueryParams,
+    pub query_type: QueryType,
+    pub state: JobState,
+    pub row_count: Option<usize>,
+    pub logs: Vec<String>,
+}
+
+impl QueuedJob {
+    fn new(id: u64, query_params: QueryParams, query_type: QueryType) -> Self {
+        Self {
+            id,
+            query_params,
+            query_type,
+            state: JobState::Pending,
+            row_count: None,
+            logs: Vec::new(),
+        }
+    }
+}
+
 /// Application state shared across Tauri commands.
 pub struct AppState {
     /// Current query parameters.
     pub query_params: QueryParams,
 
+    /// Current query type (trajectory/flights/rawdata).
+    pub query_type: QueryType,
+
     /// Chat message history.
     pub messages: Vec<ChatMessage>,
 
@@ -74,12 +132,27 @@ pub struct AppState {
     pub provider_name: String,
     pub model_name: String,
     pub error_message: Option<String>,
+    /// Where the conversational agent is in building up the current query.
+    pub agent_state: AgentState,
+
+    /// Searchable index of past query executions.
+    pub history: QueryHistoryIndex,
+
+    /// Queued jobs awaiting sequential execution, in submission order.
+    pub queue: Vec<QueuedJob>,
+    /// Next id to hand out to a queued job.
+    pub next_job_id: u64,
+    /// Whether the queue worker loop is currently draining `queue`.
+    pub queue_worker_running: bool,
+    /// Results of queued jobs, keyed by job id (kept alongside `last_result`).
+    pub queued_results: HashMap<u64, FlightData>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
             query_params: QueryParams::new(),
+            query_type: QueryType::default(),
             messages: Vec::new(),
             last_result: None,
             execution: ExecutionState::default(),
@@ -87,6 +160,12 @@ impl Default for AppState {
             provider_name: String::new(),
             model_name: String::new(),
             error_message: None,
+            agent_state: AgentState::default(),
+            history: QueryHistoryIndex::open_or_create(),
+            queue: Vec::new(),
+            next_job_id: 1,
+            queue_worker_running: false,
+            queued_results: HashMap::new(),
         }
     }
 }
@@ -101,9 +180,16 @@ impl AppState {
         self.messages.push(ChatMessage::new(role, content, msg_type));
     }
 
-    /// Clear chat messages.
+    /// Add a chat message carrying a user-friendly hint (used for "code" previews).
+    pub fn add_message_with_hint(&mut self, role: &str, content: &str, msg_type: &str, hint: &str) {
+        self.messages
+            .push(ChatMessage::with_hint(role, content, msg_type, hint));
+    }
+
+    /// Clear chat messages and reset the agent back to its starting state.
     pub fn clear_messages(&mut self) {
         self.messages.clear();
+        self.agent_state = AgentState::Collecting;
     }
 
     /// Add execution log entry.
@@ -122,6 +208,26 @@ impl AppState {
             result: None,
         };
     }
+
+    /// Snapshot the current query params/type into a new queued job.
+    pub fn enqueue_job(&mut self) -> u64 {
+        let id = self.next_job_id;
+        self.next_job_id += 1;
+        self.queue.push(QueuedJob::new(
+            id,
+            self.query_params.clone(),
+            self.query_type,
+        ));
+        id
+    }
+
+    /// Append a timestamped log line to a queued job.
+    pub fn add_job_log(&mut self, job_id: u64, message: &str) {
+        let timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
+        if let Some(job) = self.queue.iter_mut().find(|j| j.id == job_id) {
+            job.logs.push(format!("[{}] {}", timestamp, message));
+        }
+    }
 }
 
 /// Thread-safe state wrapper.